@@ -8,6 +8,49 @@ pub struct Bencher {
     codspeed: CodSpeed,
     current_file: String,
     current_bench_path: String,
+    filters: Filters,
+    list: bool,
+}
+
+/// Benchmark filters from the command line, matching the URIs as substrings, or exactly with
+/// `--exact`
+#[derive(Default)]
+struct Filters {
+    filter: Option<String>,
+    skip: Vec<String>,
+    exact: bool,
+}
+
+impl Filters {
+    fn from_args() -> Self {
+        let mut filters = Filters::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--exact" => filters.exact = true,
+                "--skip" => filters.skip.extend(args.next()),
+                _ => {
+                    if let Some(skip) = arg.strip_prefix("--skip=") {
+                        filters.skip.push(skip.to_owned());
+                    } else if !arg.starts_with('-') && filters.filter.is_none() {
+                        filters.filter = Some(arg);
+                    }
+                }
+            }
+        }
+        filters
+    }
+
+    fn is_match(&self, uri: &str) -> bool {
+        let matches = |pattern: &String| {
+            if self.exact {
+                uri == pattern
+            } else {
+                uri.contains(pattern.as_str())
+            }
+        };
+        self.filter.as_ref().is_none_or(matches) && !self.skip.iter().any(matches)
+    }
 }
 
 impl Bencher {
@@ -34,6 +77,13 @@ impl Bencher {
         let file = get_git_relative_path(self.current_file.as_str());
         let bench_path = get_formated_function_path(self.current_bench_path.as_str());
         let uri = format!("{}::{}", file.to_string_lossy(), bench_path);
+        if !self.filters.is_match(&uri) {
+            return;
+        }
+        if self.list {
+            println!("{uri}: benchmark");
+            return;
+        }
         for _ in 0..WARMUP_RUNS {
             black_box(inner());
         }
@@ -46,15 +96,21 @@ impl Bencher {
 
 impl Default for Bencher {
     fn default() -> Self {
-        println!(
-            "Harness: codspeed-bencher-compat v{}",
-            env!("CARGO_PKG_VERSION"),
-        );
+        let list = std::env::args().skip(1).any(|arg| arg == "--list");
+        // Keep the output parsable when listing benchmarks
+        if !list {
+            println!(
+                "Harness: codspeed-bencher-compat v{}",
+                env!("CARGO_PKG_VERSION"),
+            );
+        }
         Bencher {
             bytes: 0,
             codspeed: CodSpeed::new(),
             current_file: String::new(),
             current_bench_path: String::new(),
+            filters: Filters::from_args(),
+            list,
         }
     }
 }
@@ -1,11 +1,16 @@
 use crate::{
+    compare::{compare_result_files, CompareConfig},
+    config::CodSpeedConfig,
+    helpers::{parse_duration, parse_env_var, parse_shard, split_command, Shard},
+    list::{list_benches, ListConfig},
     measurement_mode::{BuildMode, MeasurementMode},
+    messages::MessageFormat,
     prelude::*,
-    run::run_benches,
+    run::{run_benches_for_modes, RunConfig},
 };
 use cargo_metadata::MetadataCommand;
 use clap::{ArgAction, Args, Parser, Subcommand};
-use std::{ffi::OsString, process::exit};
+use std::{ffi::OsString, num::NonZeroUsize, path::PathBuf, process::exit, time::Duration};
 
 use crate::build::{build_benches, BuildConfig};
 
@@ -22,7 +27,6 @@ struct Cli {
 
 impl Cli {
     pub fn run(self) -> Result<()> {
-        let metadata = MetadataCommand::new().exec()?;
         match self.command {
             Commands::Build {
                 package_filters,
@@ -32,11 +36,15 @@ impl Cli {
                 jobs,
                 no_default_features,
                 profile,
+                target,
                 locked,
                 offline,
                 frozen,
+                separate_target_dirs,
                 measurement_mode,
             } => {
+                let metadata = MetadataCommand::new().exec()?;
+                let workspace_config = CodSpeedConfig::for_workspace(&metadata)?;
                 let passthrough_flags = {
                     let mut passthrough_flags = Vec::new();
                     if all_features {
@@ -59,8 +67,18 @@ impl Cli {
                     }
                     passthrough_flags
                 };
-                let features =
-                    features.map(|f| f.split([' ', ',']).map(|s| s.to_string()).collect_vec());
+                let features = features
+                    .map(|f| f.split([' ', ',']).map(|s| s.to_string()).collect_vec())
+                    .or(workspace_config.features.clone());
+                let profile = workspace_config.resolve_profile(profile);
+                let measurement_mode = if measurement_mode.is_empty() {
+                    workspace_config
+                        .measurement_mode
+                        .clone()
+                        .unwrap_or_default()
+                } else {
+                    measurement_mode
+                };
 
                 let modes = measurement_mode.iter().map(|m| m.to_string()).join(", ");
                 eprintln!(
@@ -86,10 +104,16 @@ impl Cli {
                             package_filters: package_filters.clone(),
                             bench_target_filters: bench_target_filters.clone(),
                             features: features.clone(),
+                            all_features,
+                            no_default_features,
                             profile: profile.clone(),
+                            target: target.clone(),
                             quiet: self.quiet,
                             build_mode,
+                            separate_target_dirs: separate_target_dirs
+                                || workspace_config.separate_target_dirs.unwrap_or(false),
                             passthrough_flags: passthrough_flags.clone(),
+                            workspace_config: workspace_config.clone(),
                         },
                     )?;
                 }
@@ -97,22 +121,124 @@ impl Cli {
             }
             Commands::Run {
                 benchname,
+                bench_name_filters,
                 package_filters,
                 bench_target_filters,
                 measurement_mode,
+                save_baseline,
+                baseline,
+                keep_raw_samples,
+                message_format,
+                jobs,
+                no_fail_fast,
+                timeout,
+                target,
+                profile,
+                require_fresh,
+                wrapper,
+                env,
+                repeat,
+                interleave,
+                changed_since,
+                shard,
                 bench_args,
             } => {
-                let mode = measurement_mode.unwrap_or_default();
-                eprintln!("[cargo-codspeed] Measurement mode: {mode:?}\n");
-                run_benches(
+                let metadata = MetadataCommand::new().exec()?;
+                let workspace_config = CodSpeedConfig::for_workspace(&metadata)?;
+                let measurement_modes = if measurement_mode.is_empty() {
+                    workspace_config
+                        .measurement_mode
+                        .clone()
+                        .unwrap_or_default()
+                } else {
+                    measurement_mode
+                };
+                let measurement_modes = if measurement_modes.is_empty() {
+                    vec![MeasurementMode::default()]
+                } else {
+                    measurement_modes.into_iter().unique().collect_vec()
+                };
+                run_benches_for_modes(
+                    &metadata,
+                    RunConfig {
+                        bench_name_filter: benchname,
+                        bench_name_filters,
+                        package_filters,
+                        bench_target_filters,
+                        measurement_mode: measurement_modes[0],
+                        bench_args,
+                        save_baseline,
+                        baseline,
+                        keep_raw_samples: keep_raw_samples || codspeed::utils::keep_raw_samples(),
+                        message_format,
+                        jobs: jobs.map_or(1, NonZeroUsize::get),
+                        no_fail_fast,
+                        timeout,
+                        profile: workspace_config.resolve_profile(profile),
+                        workspace_config,
+                        target,
+                        require_fresh,
+                        wrapper: wrapper
+                            .as_deref()
+                            .map(|wrapper| split_command("wrapper", wrapper))
+                            .transpose()?
+                            .unwrap_or_default(),
+                        env,
+                        repeat: repeat.map_or(1, NonZeroUsize::get),
+                        interleave,
+                        changed_since,
+                        shard,
+                    },
+                    &measurement_modes,
+                )
+            }
+            Commands::List {
+                benchname,
+                bench_name_filters,
+                package_filters,
+                bench_target_filters,
+                measurement_mode,
+                target,
+                profile,
+            } => {
+                let metadata = MetadataCommand::new().exec()?;
+                let workspace_config = CodSpeedConfig::for_workspace(&metadata)?;
+                let measurement_mode = measurement_mode
+                    .or_else(|| {
+                        workspace_config
+                            .measurement_mode
+                            .as_ref()
+                            .and_then(|modes| modes.first().copied())
+                    })
+                    .unwrap_or_default();
+                list_benches(
                     &metadata,
-                    benchname,
-                    package_filters,
-                    bench_target_filters,
-                    mode,
-                    bench_args,
+                    ListConfig {
+                        bench_name_filter: benchname,
+                        bench_name_filters,
+                        package_filters,
+                        bench_target_filters,
+                        measurement_mode,
+                        profile: workspace_config.resolve_profile(profile),
+                        workspace_config,
+                        target,
+                    },
                 )
             }
+            // Comparing result files does not require being in a cargo project
+            Commands::Compare {
+                baseline,
+                current,
+                significance_level,
+                noise_threshold,
+            } => compare_result_files(
+                &baseline,
+                &current,
+                CompareConfig {
+                    significance_level,
+                    noise_threshold,
+                },
+            ),
         }
     }
 }
@@ -138,6 +264,18 @@ pub(crate) struct BenchTargetFilters {
     pub(crate) bench: Option<Vec<String>>,
 }
 
+/// Filters on the benchmark names, forwarded to the benchmark harnesses
+#[derive(Args, Clone)]
+pub(crate) struct BenchNameFilters {
+    /// Skip the benches whose names match this pattern, can be repeated
+    #[arg(long, value_name = "PATTERN")]
+    pub(crate) skip: Vec<String>,
+
+    /// Match the benchmark names exactly rather than with patterns
+    #[arg(long)]
+    pub(crate) exact: bool,
+}
+
 // Help headings, should mostly match the headers from cargo build --help
 const FEATURE_HELP: &str = "Feature Selection";
 const COMPILATION_HELP: &str = "Compilation Options";
@@ -145,6 +283,8 @@ const TARGET_HELP: &str = "Target Selection";
 const MANIFEST_HELP: &str = "Manifest Options";
 
 #[derive(Subcommand)]
+// Parsed once per invocation, so the size of the largest variant does not matter
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Build the benchmarks
     Build {
@@ -167,9 +307,19 @@ enum Commands {
         #[arg(short, long, help_heading = COMPILATION_HELP)]
         jobs: Option<u32>,
 
-        /// Build the benchmarks with the specified profile
-        #[arg(long, default_value = "bench", help_heading = COMPILATION_HELP)]
-        profile: String,
+        /// Build the benchmarks with the specified profile [default: bench]
+        #[arg(long, help_heading = COMPILATION_HELP)]
+        profile: Option<String>,
+
+        /// Build the benchmarks for the target triple
+        #[arg(long, value_name = "TRIPLE", help_heading = COMPILATION_HELP)]
+        target: Option<String>,
+
+        /// Build each measurement mode in its own target directory, in
+        /// `target/codspeed/build/<mode>`, so that switching between modes does not rebuild all
+        /// the dependencies. Also enabled by the `separate-target-dirs` setting.
+        #[arg(long, help_heading = COMPILATION_HELP)]
+        separate_target_dirs: bool,
 
         /// Assert that `Cargo.lock` will remain unchanged
         #[arg(long, help_heading = MANIFEST_HELP)]
@@ -203,21 +353,152 @@ enum Commands {
         /// If specified, only run benches containing this string in their names
         benchname: Option<String>,
 
+        #[command(flatten)]
+        bench_name_filters: BenchNameFilters,
+
         #[command(flatten)]
         package_filters: PackageFilters,
 
         #[command(flatten)]
         bench_target_filters: BenchTargetFilters,
 
-        /// The measurement tool to use for measuring performance.
+        /// The measurement tool(s) to use for measuring performance, each mode being run in turn.
+        /// Can be specified multiple times or comma-separated.
         /// Automatically set to `walltime` on macro runners
-        #[arg(short = 'm', long = "measurement-mode", env = "CODSPEED_RUNNER_MODE")]
-        measurement_mode: Option<MeasurementMode>,
+        #[arg(
+            short = 'm',
+            long = "measurement-mode",
+            value_delimiter = ',',
+            action = ArgAction::Append,
+            env = "CODSPEED_RUNNER_MODE"
+        )]
+        measurement_mode: Vec<MeasurementMode>,
+
+        /// Save the walltime results as a named baseline, in `target/codspeed/baselines/<NAME>.json`
+        #[arg(long, value_name = "NAME")]
+        save_baseline: Option<String>,
+
+        /// Compare the walltime results against a previously saved baseline
+        #[arg(long, value_name = "NAME")]
+        baseline: Option<String>,
+
+        /// Keep the raw per-round samples in the walltime results, in addition to the stats.
+        /// Also enabled by `CODSPEED_KEEP_RAW_SAMPLES=true`.
+        #[arg(long)]
+        keep_raw_samples: bool,
+
+        /// The output format for benchmark events
+        #[arg(long, value_enum, default_value_t)]
+        message_format: MessageFormat,
+
+        /// Number of benchmark suites to run in parallel, in simulation and memory modes only.
+        /// The output of each suite is printed once it finishes.
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<NonZeroUsize>,
+
+        /// Run all the benchmark suites regardless of failures, and report the failed ones at
+        /// the end
+        #[arg(long)]
+        no_fail_fast: bool,
+
+        /// Kill the benchmark suites running longer than this duration, e.g. `90s` or `10m`.
+        /// Defaults to the `timeout` set in `[package.metadata.codspeed]` or in
+        /// `[workspace.metadata.codspeed]`.
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        timeout: Option<Duration>,
+
+        /// Run the benchmarks built for the target triple, through the `target.<TRIPLE>.runner`
+        /// of the cargo configuration if any
+        #[arg(long, value_name = "TRIPLE")]
+        target: Option<String>,
+
+        /// Run the benchmarks built with the specified profile [default: bench]
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Fail instead of warning when the sources changed since the benchmarks were built.
+        /// Sources are compared by size and modification time, so a fresh checkout or restored
+        /// artifacts make every benchmark stale, e.g. when building and running in separate CI jobs.
+        #[arg(long)]
+        require_fresh: bool,
+
+        /// Command prefixing each benchmark invocation, e.g. `taskset -c 2` or `nice -n -5`.
+        /// Arguments are split and quoted like in a shell.
+        #[arg(long, value_name = "CMD")]
+        wrapper: Option<String>,
+
+        /// Environment variable set when running the benchmarks, can be repeated. Takes
+        /// precedence over the `env` of the configuration.
+        #[arg(long, value_name = "KEY=VALUE", value_parser = parse_env_var)]
+        env: Vec<(String, String)>,
+
+        /// Run each benchmark suite N times and merge the measurements of every run, in walltime
+        /// mode only
+        #[arg(long, value_name = "N")]
+        repeat: Option<NonZeroUsize>,
+
+        /// With --repeat, run all the suites once before running them again instead of repeating
+        /// each suite in a row
+        #[arg(long, requires = "repeat")]
+        interleave: bool,
+
+        /// Only run the benchmark suites of the packages affected by the changes since a git
+        /// reference, i.e. the packages with changed files and their dependents in the workspace
+        #[arg(long, value_name = "REF")]
+        changed_since: Option<String>,
+
+        /// Only run the benchmark suites of a shard, e.g. `1/4` for the first of four shards.
+        /// Suites are assigned to shards based on the hash of their names.
+        #[arg(long, value_name = "INDEX/TOTAL", value_parser = parse_shard)]
+        shard: Option<Shard>,
 
         /// Arguments forwarded to each benchmark binary after `--`
         #[arg(last = true)]
         bench_args: Vec<String>,
     },
+    /// List the benchmarks of the previously built benchmark targets, without running them
+    List {
+        /// If specified, only list benches containing this string in their names
+        benchname: Option<String>,
+
+        #[command(flatten)]
+        bench_name_filters: BenchNameFilters,
+
+        #[command(flatten)]
+        package_filters: PackageFilters,
+
+        #[command(flatten)]
+        bench_target_filters: BenchTargetFilters,
+
+        /// List the benchmarks built for this measurement mode, defaults to the first configured
+        /// one
+        #[arg(short = 'm', long = "measurement-mode", env = "CODSPEED_RUNNER_MODE")]
+        measurement_mode: Option<MeasurementMode>,
+
+        /// List the benchmarks built for the target triple
+        #[arg(long, value_name = "TRIPLE")]
+        target: Option<String>,
+
+        /// List the benchmarks built with the specified profile [default: bench]
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Compare two walltime result files
+    Compare {
+        /// Path to the baseline walltime results, e.g. `target/codspeed/profiles/results/<pid>.json`
+        baseline: PathBuf,
+
+        /// Path to the walltime results to compare against the baseline
+        current: PathBuf,
+
+        /// Maximum p-value for a change to be considered statistically significant
+        #[arg(long, default_value_t = CompareConfig::default().significance_level)]
+        significance_level: f64,
+
+        /// Relative change of the mean under which differences are considered as noise
+        #[arg(long, default_value_t = CompareConfig::default().noise_threshold)]
+        noise_threshold: f64,
+    },
 }
 
 pub fn run(args: impl Iterator<Item = OsString>) -> Result<()> {
@@ -1,20 +1,29 @@
 use crate::{
     app::{BenchTargetFilters, PackageFilters},
-    helpers::{clear_dir, get_codspeed_target_dir},
+    config::CodSpeedConfig,
+    fingerprint::{sources_fingerprint, with_lockfile},
+    helpers::{clear_dir, get_cargo_build_dir, get_codspeed_target_dir},
+    manifest::{BuildManifest, FeatureSelection, ManifestBench},
     measurement_mode::BuildMode,
     prelude::*,
 };
 use anyhow::Context;
-use cargo_metadata::{camino::Utf8PathBuf, Message, Metadata, TargetKind};
-use std::collections::HashMap;
+use cargo_metadata::{camino::Utf8PathBuf, Message, Metadata, Package, TargetKind};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::process::{exit, Command, Stdio};
 
 struct BuildOptions<'a> {
     bench_target_filters: BenchTargetFilters,
     package_filters: PackageFilters,
     features: &'a Option<Vec<String>>,
+    all_features: bool,
+    no_default_features: bool,
     profile: &'a str,
+    target: Option<&'a str>,
+    separate_target_dirs: bool,
     passthrough_flags: &'a Vec<String>,
+    workspace_config: &'a CodSpeedConfig,
 }
 
 struct BuiltBench {
@@ -28,10 +37,16 @@ pub struct BuildConfig {
     pub package_filters: PackageFilters,
     pub bench_target_filters: BenchTargetFilters,
     pub features: Option<Vec<String>>,
+    pub all_features: bool,
+    pub no_default_features: bool,
     pub profile: String,
+    pub target: Option<String>,
     pub quiet: bool,
     pub build_mode: BuildMode,
+    /// Build each build mode in its own cargo target directory
+    pub separate_target_dirs: bool,
     pub passthrough_flags: Vec<String>,
+    pub workspace_config: CodSpeedConfig,
 }
 
 fn get_bench_harness_value(
@@ -78,7 +93,90 @@ fn get_bench_harness_value(
     Ok(harness_enabled)
 }
 
+/// A benchmark target that cargo skips because some of its `required-features` are not enabled
+struct BenchMissingFeatures {
+    package: String,
+    bench: String,
+    missing_features: Vec<String>,
+}
+
 impl BuildOptions<'_> {
+    /// Features enabled for a package, including the ones enabled by other features
+    fn enabled_features(&self, package: &Package) -> HashSet<String> {
+        let mut pending = self
+            .features
+            .iter()
+            .flatten()
+            .filter_map(|feature| match feature.split_once('/') {
+                Some((package_name, feature)) => {
+                    (package_name == package.name.as_str()).then(|| feature.to_string())
+                }
+                None => Some(feature.clone()),
+            })
+            .collect_vec();
+        if !self.no_default_features {
+            pending.push("default".to_string());
+        }
+
+        let mut enabled = HashSet::new();
+        while let Some(feature) = pending.pop() {
+            if let Some(implied_features) = package.features.get(&feature) {
+                pending.extend(
+                    implied_features
+                        .iter()
+                        // Features of dependencies do not enable features of the package
+                        .filter(|implied| !implied.contains('/'))
+                        .map(|implied| implied.trim_start_matches("dep:").to_string())
+                        .filter(|implied| !enabled.contains(implied)),
+                );
+            }
+            enabled.insert(feature);
+        }
+        enabled
+    }
+
+    /// Lists the benchmark targets that cargo will silently skip because of their
+    /// `required-features`
+    ///
+    /// Targets selected with `--bench` are not listed, since cargo fails with an explicit error
+    /// for those.
+    fn benches_missing_required_features(
+        &self,
+        metadata: &Metadata,
+    ) -> Result<Vec<BenchMissingFeatures>> {
+        if self.all_features || self.bench_target_filters.bench.is_some() {
+            return Ok(vec![]);
+        }
+
+        let mut benches = vec![];
+        for package in self.package_filters.packages_from_flags(metadata)? {
+            let config = CodSpeedConfig::for_package(package)?.or(self.workspace_config.clone());
+            let enabled_features = self.enabled_features(package);
+            for target in &package.targets {
+                if !target.is_kind(TargetKind::Bench) || config.excludes_bench(&target.name) {
+                    continue;
+                }
+                let missing_features = target
+                    .required_features
+                    .iter()
+                    // Features of dependencies are not checked
+                    .filter(|feature| {
+                        !feature.contains('/') && !enabled_features.contains(*feature)
+                    })
+                    .cloned()
+                    .collect_vec();
+                if !missing_features.is_empty() {
+                    benches.push(BenchMissingFeatures {
+                        package: package.name.to_string(),
+                        bench: target.name.clone(),
+                        missing_features,
+                    });
+                }
+            }
+        }
+        Ok(benches)
+    }
+
     /// Builds the benchmarks by invoking cargo
     /// Returns a list of built benchmarks, with path to associated executables
     fn build(
@@ -88,8 +186,9 @@ impl BuildOptions<'_> {
         build_mode: BuildMode,
     ) -> Result<Vec<BuiltBench>> {
         let workspace_packages = metadata.workspace_packages();
+        let benches_missing_features = self.benches_missing_required_features(metadata)?;
 
-        let mut cargo = self.build_command(build_mode);
+        let mut cargo = self.build_command(metadata, build_mode);
         if quiet {
             cargo.arg("--quiet");
         }
@@ -110,16 +209,23 @@ impl BuildOptions<'_> {
         let mut bench_targets_with_default_harness = Vec::new();
         let mut manifest_cache = HashMap::new();
 
-        let package_names = self
+        let package_configs = self
             .package_filters
             .packages_from_flags(metadata)
+            .and_then(|packages| {
+                packages
+                    .into_iter()
+                    .map(|package| {
+                        let config =
+                            CodSpeedConfig::for_package(package)?.or(self.workspace_config.clone());
+                        Ok((package.name.to_string(), config))
+                    })
+                    .collect::<Result<HashMap<_, _>>>()
+            })
             .inspect_err(|_e| {
                 // Avoid leaving an orphan cargo process, even if something went wrong
                 cargo.wait().expect("Could not get cargo's exit status");
-            })?
-            .into_iter()
-            .map(|t| t.name.clone())
-            .collect::<Vec<_>>();
+            })?;
 
         for message in Message::parse_stream(reader) {
             match message.expect("Failed to parse message") {
@@ -140,7 +246,22 @@ impl BuildOptions<'_> {
 
                     let bench_target_name = artifact.target.name;
 
-                    let add_bench_to_codspeed_dir = package_names.iter().contains(&package.name);
+                    let add_bench_to_codspeed_dir = match package_configs.get(package.name.as_str())
+                    {
+                        // Benchmark targets selected with --bench are never excluded
+                        Some(config)
+                            if self.bench_target_filters.bench.is_none()
+                                && config.excludes_bench(&bench_target_name) =>
+                        {
+                            eprintln!(
+                                "Skipping excluded benchmark `{bench_target_name}` in package `{}`",
+                                package.name
+                            );
+                            false
+                        }
+                        Some(_) => true,
+                        None => false,
+                    };
 
                     if add_bench_to_codspeed_dir {
                         if get_bench_harness_value(
@@ -190,6 +311,31 @@ Either disable the default harness by adding `harness = false` to the correspond
 See `cargo codspeed build --help` for more information.");
         }
 
+        if !benches_missing_features.is_empty() {
+            let targets_list = benches_missing_features
+                .into_iter()
+                .map(|bench| {
+                    format!(
+                        "  - `{}` in package `{}`, requires {}",
+                        bench.bench,
+                        bench.package,
+                        bench
+                            .missing_features
+                            .iter()
+                            .map(|f| format!("`{f}`"))
+                            .join(", ")
+                    )
+                })
+                .join("\n");
+
+            eprintln!("\
+Warning: The following benchmark targets were skipped because of their `required-features`:
+{targets_list}
+
+Enable the missing features with `cargo codspeed build --features <FEATURES>`, or build them with `--all-features`.
+");
+        }
+
         for built_bench in &built_benches {
             eprintln!(
                 "Built benchmark `{}` in package `{}`",
@@ -212,23 +358,7 @@ See `cargo codspeed build --help` for more information.");
     /// We use the cargo built-in config mechanism to set the flags if the user has not set
     /// `RUSTFLAGS`.
     fn add_rust_flags(&self, cargo: &mut Command, build_mode: BuildMode) {
-        let mut flags = vec![
-            // Add debug info (equivalent to -g)
-            "-Cdebuginfo=2".to_owned(),
-            // Prevent debug info stripping
-            // https://doc.rust-lang.org/cargo/reference/profiles.html#release
-            // According to cargo docs, for release profile which we default to:
-            // `strip = "none"` and `debug = false`.
-            // In practice, if we set debug info through RUSTFLAGS, cargo still strips them, most
-            // likely because debug = false in the release profile.
-            // We also need to disable stripping through rust flags.
-            "-Cstrip=none".to_owned(),
-        ];
-
-        // Add the codspeed cfg flag if the benchmark should only run once
-        if build_mode == BuildMode::Analysis {
-            flags.push("--cfg=codspeed".to_owned());
-        }
+        let flags = codspeed_rust_flags(build_mode);
 
         match std::env::var("RUSTFLAGS") {
             Result::Ok(existing_rustflags) => {
@@ -252,10 +382,16 @@ See `cargo codspeed build --help` for more information.");
 
     /// Generates a subcommand to build the benchmarks by invoking cargo and forwarding the filters
     /// This command explicitly ignores the `self.benches`: all benches are built
-    fn build_command(&self, build_mode: BuildMode) -> Command {
+    fn build_command(&self, metadata: &Metadata, build_mode: BuildMode) -> Command {
         let mut cargo = Command::new("cargo");
         cargo.arg("build");
 
+        if self.separate_target_dirs {
+            cargo
+                .arg("--target-dir")
+                .arg(get_cargo_build_dir(metadata, build_mode));
+        }
+
         if let Some(bench_target_filters) = &self.bench_target_filters.bench {
             for bench_target_filter in bench_target_filters {
                 cargo.args(["--bench", bench_target_filter]);
@@ -274,12 +410,39 @@ See `cargo codspeed build --help` for more information.");
 
         cargo.arg("--profile").arg(self.profile);
 
+        if let Some(target) = self.target {
+            cargo.arg("--target").arg(target);
+        }
+
         self.package_filters.add_cargo_args(&mut cargo);
 
         cargo
     }
 }
 
+/// Flags added to the rust flags of the benchmarks
+fn codspeed_rust_flags(build_mode: BuildMode) -> Vec<String> {
+    let mut flags = vec![
+        // Add debug info (equivalent to -g)
+        "-Cdebuginfo=2".to_owned(),
+        // Prevent debug info stripping
+        // https://doc.rust-lang.org/cargo/reference/profiles.html#release
+        // According to cargo docs, for release profile which we default to:
+        // `strip = "none"` and `debug = false`.
+        // In practice, if we set debug info through RUSTFLAGS, cargo still strips them, most
+        // likely because debug = false in the release profile.
+        // We also need to disable stripping through rust flags.
+        "-Cstrip=none".to_owned(),
+    ];
+
+    // Add the codspeed cfg flag if the benchmark should only run once
+    if build_mode == BuildMode::Analysis {
+        flags.push("--cfg=codspeed".to_owned());
+    }
+
+    flags
+}
+
 impl PackageFilters {
     fn add_cargo_args(&self, cargo: &mut Command) {
         if self.workspace {
@@ -302,12 +465,29 @@ impl PackageFilters {
 
 pub fn build_benches(metadata: &Metadata, config: BuildConfig) -> Result<()> {
     let build_mode = config.build_mode;
+    // Computed before building, so that the sources edited during the build are considered stale
+    let sources_fingerprints = config
+        .package_filters
+        .packages_from_flags(metadata)?
+        .into_iter()
+        .map(|package| {
+            Ok((
+                package.name.to_string(),
+                sources_fingerprint(metadata, package)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
     let built_benches = BuildOptions {
         bench_target_filters: config.bench_target_filters,
-        package_filters: config.package_filters,
+        package_filters: config.package_filters.clone(),
         features: &config.features,
+        all_features: config.all_features,
+        no_default_features: config.no_default_features,
         profile: &config.profile,
+        target: config.target.as_deref(),
+        separate_target_dirs: config.separate_target_dirs,
         passthrough_flags: &config.passthrough_flags,
+        workspace_config: &config.workspace_config,
     }
     .build(metadata, config.quiet, build_mode)?;
 
@@ -318,7 +498,12 @@ pub fn build_benches(metadata: &Metadata, config: BuildConfig) -> Result<()> {
         );
     }
 
-    let codspeed_target_dir = get_codspeed_target_dir(metadata, build_mode);
+    let codspeed_target_dir = get_codspeed_target_dir(
+        metadata,
+        build_mode,
+        config.target.as_deref(),
+        &config.profile,
+    );
     let built_bench_count = built_benches.len();
 
     // Create and clear packages codspeed target directories
@@ -331,15 +516,61 @@ pub fn build_benches(metadata: &Metadata, config: BuildConfig) -> Result<()> {
         clear_dir(&target_dir)?;
     }
 
+    // The lockfile is added after building, since cargo may update it
+    let source_fingerprints = sources_fingerprints
+        .into_iter()
+        .map(|(package, fingerprint)| Ok((package, with_lockfile(metadata, fingerprint)?)))
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    // Keep the benchmarks of the packages that were not rebuilt
+    let mut manifest = BuildManifest::new(metadata, &config.profile, config.target.as_deref());
+    if let Some(previous_manifest) = BuildManifest::read(&codspeed_target_dir).ok().flatten() {
+        manifest.benches = previous_manifest
+            .benches
+            .into_iter()
+            .filter(|bench| {
+                !built_benches
+                    .iter()
+                    .any(|built| built.package == bench.package)
+                    && codspeed_target_dir.join(&bench.executable_path).exists()
+            })
+            .collect();
+    }
+    let features = FeatureSelection {
+        features: config.features.clone().unwrap_or_default(),
+        all_features: config.all_features,
+        no_default_features: config.no_default_features,
+    };
+    let rustflags = std::env::var("RUSTFLAGS")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(String::from)
+        .chain(codspeed_rust_flags(build_mode))
+        .collect_vec();
+
     // Copy built artifacts to codspeed target directory
     for built_bench in built_benches {
-        let codspeed_target_package_dir = codspeed_target_dir.clone().join(&built_bench.package);
+        let executable_path = Path::new(&built_bench.package).join(&built_bench.bench);
 
         std::fs::copy(
             built_bench.executable_path,
-            codspeed_target_package_dir.join(built_bench.bench),
+            codspeed_target_dir.join(&executable_path),
         )?;
+
+        manifest.benches.push(ManifestBench {
+            source_fingerprint: source_fingerprints.get(&built_bench.package).cloned(),
+            package: built_bench.package,
+            bench: built_bench.bench,
+            executable_path,
+            features: features.clone(),
+            rustflags: rustflags.clone(),
+        });
     }
+    // Cargo reports the artifacts in the order they are built, which varies between builds
+    manifest
+        .benches
+        .sort_by(|a, b| (&a.package, &a.bench).cmp(&(&b.package, &b.bench)));
+    manifest.write(&codspeed_target_dir)?;
 
     eprintln!("Built {built_bench_count} benchmark suite(s)");
 
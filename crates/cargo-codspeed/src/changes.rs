@@ -0,0 +1,141 @@
+use crate::prelude::*;
+use anyhow::Context;
+use cargo_metadata::{Metadata, Package, PackageId};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Files and directories at the root of the workspace whose changes can affect every package
+const WORKSPACE_FILES: &[&str] = &[
+    "Cargo.toml",
+    "Cargo.lock",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+    ".cargo",
+];
+
+/// Finds the names of the workspace packages whose benchmarks could be affected by the changes
+/// since a git reference, including uncommitted and untracked files
+///
+/// A package is affected if one of its files changed, or if one of its local dependencies, i.e.
+/// the ones not coming from a registry or a git repository, is affected. Changes to the manifest,
+/// lockfile, toolchain or cargo configuration at the root of the workspace affect every package.
+pub fn packages_affected_since(metadata: &Metadata, git_ref: &str) -> Result<HashSet<String>> {
+    let workspace_root = canonical(metadata.workspace_root.as_std_path());
+    let changed_files = changed_files_since(&workspace_root, git_ref)?;
+
+    let workspace_changed = changed_files.iter().any(|file| {
+        file.strip_prefix(&workspace_root)
+            .is_ok_and(|relative| WORKSPACE_FILES.iter().any(|f| relative.starts_with(f)))
+    });
+    if workspace_changed {
+        return Ok(metadata
+            .workspace_packages()
+            .into_iter()
+            .map(|package| package.name.to_string())
+            .collect());
+    }
+
+    let local_package_dirs = metadata
+        .packages
+        .iter()
+        .filter(|package| package.source.is_none())
+        .filter_map(|package| {
+            Some((
+                package,
+                canonical(package.manifest_path.parent()?.as_std_path()),
+            ))
+        })
+        .collect_vec();
+    let mut pending = changed_files
+        .iter()
+        .filter_map(|file| owning_package(&local_package_dirs, file))
+        .map(|package| &package.id)
+        .collect_vec();
+    if !changed_files.is_empty() && pending.is_empty() {
+        eprintln!(
+            "Warning: None of the {} file(s) changed since `{git_ref}` belong to a package of the workspace",
+            changed_files.len()
+        );
+    }
+
+    let mut dependents: HashMap<&PackageId, Vec<&PackageId>> = HashMap::new();
+    for node in metadata.resolve.iter().flat_map(|resolve| &resolve.nodes) {
+        for dep in &node.deps {
+            dependents.entry(&dep.pkg).or_default().push(&node.id);
+        }
+    }
+    let mut affected = HashSet::new();
+    while let Some(id) = pending.pop() {
+        if affected.insert(id) {
+            pending.extend(dependents.get(id).into_iter().flatten());
+        }
+    }
+
+    Ok(metadata
+        .workspace_packages()
+        .into_iter()
+        .filter(|package| affected.contains(&package.id))
+        .map(|package| package.name.to_string())
+        .collect())
+}
+
+/// Package with the deepest directory containing the file, if any
+fn owning_package<'a>(package_dirs: &[(&'a Package, PathBuf)], file: &Path) -> Option<&'a Package> {
+    package_dirs
+        .iter()
+        .filter(|(_, dir)| file.starts_with(dir))
+        .max_by_key(|(_, dir)| dir.components().count())
+        .map(|(package, _)| *package)
+}
+
+/// Resolves the symlinks of a path, so that the paths given by git and cargo can be compared
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Absolute paths of the files changed since the git reference, and of the untracked files
+fn changed_files_since(workspace_root: &Path, git_ref: &str) -> Result<Vec<PathBuf>> {
+    let git = |args: &[&str]| -> Result<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(workspace_root)
+            .output()
+            .context("Failed to execute git")?;
+        if !output.status.success() {
+            bail!(
+                "Failed to list the files changed since `{git_ref}`: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    let repository_root = canonical(Path::new(git(&["rev-parse", "--show-toplevel"])?.trim()));
+    // Without renames, both the old and the new paths of a moved file are listed. With `-z`, the
+    // paths are separated by NUL characters and not quoted, even when they are not plain ASCII.
+    let changed = git(&[
+        "diff",
+        "--name-only",
+        "--no-renames",
+        "-z",
+        "--end-of-options",
+        git_ref,
+        "--",
+    ])?;
+    let untracked = git(&[
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+        "--full-name",
+        "-z",
+    ])?;
+    Ok(changed
+        .split('\0')
+        .chain(untracked.split('\0'))
+        .filter(|file| !file.is_empty())
+        .map(|file| repository_root.join(file))
+        .collect())
+}
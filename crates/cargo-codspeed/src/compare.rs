@@ -0,0 +1,344 @@
+use crate::{helpers::format_duration_ns, prelude::*};
+use anyhow::Context;
+use codspeed::walltime_results::{WalltimeBenchmark, WalltimeResults};
+use statrs::distribution::{ContinuousCDF, StudentsT};
+use std::{io::Write, path::Path};
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompareConfig {
+    /// Maximum p-value of the Welch's t-test for a change to be considered significant
+    pub significance_level: f64,
+    /// Relative change of the mean under which a difference is considered as noise
+    pub noise_threshold: f64,
+}
+
+impl Default for CompareConfig {
+    fn default() -> Self {
+        Self {
+            significance_level: 0.05,
+            noise_threshold: 0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Improved,
+    Regressed,
+    NoChange,
+    InsufficientData,
+    New,
+    Removed,
+}
+
+impl Verdict {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Improved => "improved",
+            Verdict::Regressed => "regressed",
+            Verdict::NoChange => "no change",
+            Verdict::InsufficientData => "insufficient data",
+            Verdict::New => "new",
+            Verdict::Removed => "removed",
+        }
+    }
+}
+
+struct ComparisonRow<'a> {
+    benchmark: &'a WalltimeBenchmark,
+    baseline: Option<&'a WalltimeBenchmark>,
+    current: Option<&'a WalltimeBenchmark>,
+    verdict: Verdict,
+}
+
+pub fn read_walltime_results(path: &Path) -> Result<WalltimeResults> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open walltime results at {}", path.display()))?;
+    serde_json::from_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("Failed to parse walltime results at {}", path.display()))
+}
+
+pub fn compare_result_files(
+    baseline_path: &Path,
+    current_path: &Path,
+    config: CompareConfig,
+) -> Result<()> {
+    let baseline = read_walltime_results(baseline_path)?;
+    let current = read_walltime_results(current_path)?;
+    print_comparison(&mut std::io::stdout(), &baseline, &current, config)?;
+    Ok(())
+}
+
+/// Prints a per-benchmark diff table between two walltime result sets.
+///
+/// Benchmarks are matched on their URI. A change is reported as an improvement or a regression
+/// only if the Welch's t-test computed on the stored stats is significant, and the relative
+/// change of the mean exceeds the noise threshold.
+pub fn print_comparison(
+    out: &mut impl Write,
+    baseline: &WalltimeResults,
+    current: &WalltimeResults,
+    config: CompareConfig,
+) -> Result<()> {
+    let mut rows = current
+        .benchmarks()
+        .iter()
+        .map(|current_bench| {
+            let baseline_bench = baseline
+                .benchmarks()
+                .iter()
+                .find(|b| b.uri() == current_bench.uri());
+            let verdict = match baseline_bench {
+                Some(baseline_bench) => verdict(baseline_bench, current_bench, config),
+                None => Verdict::New,
+            };
+            ComparisonRow {
+                benchmark: current_bench,
+                baseline: baseline_bench,
+                current: Some(current_bench),
+                verdict,
+            }
+        })
+        .collect_vec();
+    rows.extend(
+        baseline
+            .benchmarks()
+            .iter()
+            .filter(|b| !current.benchmarks().iter().any(|c| c.uri() == b.uri()))
+            .map(|baseline_bench| ComparisonRow {
+                benchmark: baseline_bench,
+                baseline: Some(baseline_bench),
+                current: None,
+                verdict: Verdict::Removed,
+            }),
+    );
+
+    if rows.is_empty() {
+        eprintln!("No walltime benchmarks to compare");
+        return Ok(());
+    }
+
+    let header = [
+        "Benchmark",
+        "Baseline (median)",
+        "Current (median)",
+        "Median change",
+        "Mean change",
+        "Slope change",
+        "Verdict",
+    ]
+    .map(String::from);
+    // Benchmarks are labeled with their URI when their name is not unique, e.g. when benchmarks
+    // in different files have the same name
+    let label = |row: &ComparisonRow| {
+        let name = row.benchmark.name();
+        if rows.iter().filter(|r| r.benchmark.name() == name).count() > 1 {
+            row.benchmark.uri().to_string()
+        } else {
+            name.to_string()
+        }
+    };
+    let table = rows
+        .iter()
+        .map(|row| {
+            let median = |b: Option<&WalltimeBenchmark>| {
+                b.map(|b| format_duration_ns(b.median_ns()))
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            let change = |f: fn(&WalltimeBenchmark) -> f64| match (row.baseline, row.current) {
+                (Some(baseline), Some(current)) => format_change(f(baseline), f(current)),
+                _ => "-".to_string(),
+            };
+            [
+                label(row),
+                median(row.baseline),
+                median(row.current),
+                change(WalltimeBenchmark::median_ns),
+                change(WalltimeBenchmark::mean_ns),
+                change(WalltimeBenchmark::slope_ns),
+                row.verdict.as_str().to_string(),
+            ]
+        })
+        .collect_vec();
+
+    let widths = (0..header.len())
+        .map(|i| {
+            std::iter::once(&header)
+                .chain(&table)
+                .map(|line| line[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect_vec();
+    let format_line = |line: &[String; 7]| {
+        line.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    writeln!(out, "{}", format_line(&header))?;
+    writeln!(
+        out,
+        "{}",
+        widths.iter().map(|width| "-".repeat(*width)).join("  ")
+    )?;
+    for line in &table {
+        writeln!(out, "{}", format_line(line))?;
+    }
+
+    let count = |verdict: Verdict| rows.iter().filter(|r| r.verdict == verdict).count();
+    eprintln!(
+        "\nCompared {} benchmark(s): {} improved, {} regressed, {} unchanged",
+        rows.len(),
+        count(Verdict::Improved),
+        count(Verdict::Regressed),
+        count(Verdict::NoChange),
+    );
+    Ok(())
+}
+
+fn verdict(
+    baseline: &WalltimeBenchmark,
+    current: &WalltimeBenchmark,
+    config: CompareConfig,
+) -> Verdict {
+    let Some(p_value) = welch_t_test_p_value(baseline, current) else {
+        return Verdict::InsufficientData;
+    };
+    let relative_change = (current.mean_ns() - baseline.mean_ns()) / baseline.mean_ns();
+    // The relative change is not defined when the baseline mean is 0
+    if !relative_change.is_finite() {
+        return Verdict::InsufficientData;
+    }
+    if p_value >= config.significance_level || relative_change.abs() <= config.noise_threshold {
+        Verdict::NoChange
+    } else if relative_change > 0.0 {
+        Verdict::Regressed
+    } else {
+        Verdict::Improved
+    }
+}
+
+/// Two-sided Welch's t-test on the means, computed from the summary statistics
+///
+/// Returns `None` when a benchmark does not have enough rounds to estimate its variance.
+fn welch_t_test_p_value(baseline: &WalltimeBenchmark, current: &WalltimeBenchmark) -> Option<f64> {
+    if baseline.rounds() < 2 || current.rounds() < 2 {
+        return None;
+    }
+    let baseline_rounds = baseline.rounds() as f64;
+    let current_rounds = current.rounds() as f64;
+    let baseline_var = baseline.stdev_ns().powi(2) / baseline_rounds;
+    let current_var = current.stdev_ns().powi(2) / current_rounds;
+    let mean_diff = current.mean_ns() - baseline.mean_ns();
+
+    let standard_error = (baseline_var + current_var).sqrt();
+    if standard_error < f64::EPSILON {
+        return Some(if mean_diff.abs() < f64::EPSILON {
+            1.0
+        } else {
+            0.0
+        });
+    }
+
+    let t = mean_diff / standard_error;
+    // Welch–Satterthwaite equation
+    let degrees_of_freedom = (baseline_var + current_var).powi(2)
+        / (baseline_var.powi(2) / (baseline_rounds - 1.0)
+            + current_var.powi(2) / (current_rounds - 1.0));
+    let distribution = StudentsT::new(0.0, 1.0, degrees_of_freedom).ok()?;
+    Some(2.0 * (1.0 - distribution.cdf(t.abs())))
+}
+
+fn format_change(baseline: f64, current: f64) -> String {
+    if baseline < f64::EPSILON {
+        return "-".to_string();
+    }
+    format!("{:+.2}%", (current - baseline) / baseline * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn benchmark(name: &str, uri: &str, mean_ns: f64) -> WalltimeBenchmark {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "uri": uri,
+            "config": {
+                "warmup_time_ns": null,
+                "min_round_time_ns": null,
+                "max_time_ns": null,
+                "max_rounds": null
+            },
+            "stats": {
+                "min_ns": mean_ns,
+                "max_ns": mean_ns,
+                "mean_ns": mean_ns,
+                "stdev_ns": 1.0,
+                "q1_ns": mean_ns,
+                "median_ns": mean_ns,
+                "q3_ns": mean_ns,
+                "rounds": 100,
+                "total_time": 1.0,
+                "iqr_outlier_rounds": 0,
+                "stdev_outlier_rounds": 0,
+                "iter_per_round": 1,
+                "warmup_iters": 0
+            }
+        }))
+        .unwrap()
+    }
+
+    fn results(benchmarks: Vec<WalltimeBenchmark>) -> WalltimeResults {
+        serde_json::from_value(serde_json::json!({
+            "creator": { "name": "codspeed-rust", "version": "0.0.0", "pid": 0 },
+            "instrument": { "type": "walltime" },
+            "benchmarks": benchmarks,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verdict_zero_baseline_mean() {
+        let baseline = benchmark("fib", "benches/bench.rs::fib", 0.0);
+        let current = benchmark("fib", "benches/bench.rs::fib", 1000.0);
+        assert_eq!(
+            verdict(&baseline, &current, CompareConfig::default()),
+            Verdict::InsufficientData
+        );
+        assert_eq!(
+            verdict(&baseline, &baseline, CompareConfig::default()),
+            Verdict::InsufficientData
+        );
+    }
+
+    #[test]
+    fn test_comparison_labels_duplicate_names_with_uri() {
+        let benchmarks = || {
+            vec![
+                benchmark("fib", "benches/a.rs::fib", 1000.0),
+                benchmark("fib", "benches/b.rs::fib", 1000.0),
+                benchmark("sort", "benches/a.rs::sort", 1000.0),
+            ]
+        };
+        let mut out = Vec::new();
+        print_comparison(
+            &mut out,
+            &results(benchmarks()),
+            &results(benchmarks()),
+            CompareConfig::default(),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let labels = out
+            .lines()
+            .skip(2)
+            .map(|line| line.split_whitespace().next().unwrap())
+            .collect_vec();
+        assert_eq!(labels, ["benches/a.rs::fib", "benches/b.rs::fib", "sort"]);
+    }
+}
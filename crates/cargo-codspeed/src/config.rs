@@ -0,0 +1,200 @@
+use crate::{helpers::parse_duration, measurement_mode::MeasurementMode, prelude::*};
+use cargo_metadata::{Metadata, Package};
+use serde::{de::IgnoredAny, Deserialize, Deserializer};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Settings of the `[workspace.metadata.codspeed]` and `[package.metadata.codspeed]` tables
+///
+/// Command line options always take precedence over these settings, and the settings of a
+/// package take precedence over the ones of the workspace. Unknown keys are ignored with a
+/// warning, since they can be read by other tools or by a newer version of cargo-codspeed.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CodSpeedConfig {
+    /// Measurement modes to build and run, `list` only uses the first one
+    pub measurement_mode: Option<Vec<MeasurementMode>>,
+    /// Features to activate when building the benchmarks
+    pub features: Option<Vec<String>>,
+    /// Profile to build the benchmarks with
+    pub profile: Option<String>,
+    /// Whether each build mode is built in its own cargo target directory, `false` by default
+    pub separate_target_dirs: Option<bool>,
+    /// Benchmark targets that are neither built nor run, unless selected with `--bench`
+    pub exclude_benches: Option<Vec<String>>,
+    /// Arguments forwarded to the benchmark binaries, before the ones given after `--`
+    pub bench_args: Option<Vec<String>>,
+    /// Timeout of each benchmark suite
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub timeout: Option<Duration>,
+    /// Environment variables set when running the benchmark binaries, in addition to the ones of
+    /// the workspace
+    pub env: Option<BTreeMap<String, String>>,
+    /// Settings of individual benchmark targets, by name
+    pub benches: Option<BTreeMap<String, BenchTargetConfig>>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+/// Settings of a benchmark target, from the `benches.<name>` table of the CodSpeed configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BenchTargetConfig {
+    /// Environment variables set in addition to the ones of the package
+    pub env: Option<BTreeMap<String, String>>,
+    /// Arguments forwarded to the benchmark binary, replacing the `bench-args` of the package
+    pub bench_args: Option<Vec<String>>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error> {
+    let duration = String::deserialize(deserializer)?;
+    parse_duration(&duration)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+impl CodSpeedConfig {
+    fn from_metadata(metadata: &serde_json::Value, table: &str) -> Result<Self> {
+        match metadata.get("codspeed") {
+            Some(config) => serde_json::from_value(config.clone())
+                .map_err(|e| anyhow!("Invalid CodSpeed configuration in {table}: {e}")),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Reads the settings applying to the whole workspace
+    ///
+    /// These come from `[workspace.metadata.codspeed]`, overridden by the
+    /// `[package.metadata.codspeed]` table of the root package if any, so that single package
+    /// projects do not need a `[workspace]` table. Only these tables are used for the measurement
+    /// modes, the features, the profile and the target directories.
+    ///
+    /// The tables of all the workspace packages are checked here, warning about the unknown keys
+    /// and the workspace settings set by other packages, so that each warning is printed once.
+    pub fn for_workspace(metadata: &Metadata) -> Result<Self> {
+        let workspace_table = "`[workspace.metadata.codspeed]`";
+        let workspace_config = Self::from_metadata(&metadata.workspace_metadata, workspace_table)?;
+        workspace_config.warn_unknown_keys(workspace_table);
+
+        let root_package = metadata.root_package();
+        for package in metadata.workspace_packages() {
+            // Invalid tables are reported when the package is built or run
+            let Result::Ok(config) = Self::for_package(package) else {
+                continue;
+            };
+            config.warn_unknown_keys(&Self::package_table(package));
+            if root_package.is_some_and(|root_package| root_package.id == package.id) {
+                continue;
+            }
+            for key in config.workspace_keys() {
+                eprintln!(
+                    "Warning: `{key}` is ignored in {}, it is only read from {workspace_table} or from the root package of the workspace",
+                    Self::package_table(package)
+                );
+            }
+        }
+
+        Ok(match root_package {
+            Some(root_package) => Self::for_package(root_package)?.or(workspace_config),
+            None => workspace_config,
+        })
+    }
+
+    /// Reads the `[package.metadata.codspeed]` table of a package
+    pub fn for_package(package: &Package) -> Result<Self> {
+        Self::from_metadata(&package.metadata, &Self::package_table(package))
+    }
+
+    fn package_table(package: &Package) -> String {
+        format!("`[package.metadata.codspeed]` of package {}", package.name)
+    }
+
+    fn warn_unknown_keys(&self, table: &str) {
+        let bench_keys = self.benches.iter().flatten().flat_map(|(name, config)| {
+            config
+                .unknown
+                .keys()
+                .map(move |key| format!("benches.{name}.{key}"))
+        });
+        for key in self.unknown.keys().cloned().chain(bench_keys) {
+            eprintln!("Warning: Unknown key `{key}` in {table}, it is ignored");
+        }
+    }
+
+    /// Keys that are set and only read for the whole workspace
+    fn workspace_keys(&self) -> Vec<&'static str> {
+        [
+            ("measurement-mode", self.measurement_mode.is_some()),
+            ("features", self.features.is_some()),
+            ("profile", self.profile.is_some()),
+            ("separate-target-dirs", self.separate_target_dirs.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(key, is_set)| is_set.then_some(key))
+        .collect()
+    }
+
+    /// Fills the settings that are not set with the ones of `fallback`, and merges the `env`
+    /// tables, the variables of `self` taking precedence
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            measurement_mode: self.measurement_mode.or(fallback.measurement_mode),
+            features: self.features.or(fallback.features),
+            profile: self.profile.or(fallback.profile),
+            separate_target_dirs: self.separate_target_dirs.or(fallback.separate_target_dirs),
+            exclude_benches: self.exclude_benches.or(fallback.exclude_benches),
+            bench_args: self.bench_args.or(fallback.bench_args),
+            timeout: self.timeout.or(fallback.timeout),
+            env: match (fallback.env, self.env) {
+                (Some(mut env), Some(overrides)) => {
+                    env.extend(overrides);
+                    Some(env)
+                }
+                (env, overrides) => overrides.or(env),
+            },
+            benches: self.benches.or(fallback.benches),
+            unknown: BTreeMap::new(),
+        }
+    }
+
+    /// Profile selected on the command line, else in the configuration, else `bench`
+    pub fn resolve_profile(&self, profile: Option<String>) -> String {
+        profile
+            .or_else(|| self.profile.clone())
+            .unwrap_or_else(|| "bench".to_string())
+    }
+
+    pub fn excludes_bench(&self, bench_target_name: &str) -> bool {
+        self.exclude_benches
+            .as_ref()
+            .is_some_and(|excluded| excluded.iter().any(|name| name == bench_target_name))
+    }
+
+    /// Environment variables set when running a benchmark target, the ones of the target taking
+    /// precedence
+    pub fn bench_env(&self, bench_target_name: &str) -> BTreeMap<String, String> {
+        let mut env = self.env.clone().unwrap_or_default();
+        if let Some(target_env) = self
+            .bench_target_config(bench_target_name)
+            .and_then(|config| config.env.as_ref())
+        {
+            env.extend(target_env.clone());
+        }
+        env
+    }
+
+    /// Arguments forwarded to a benchmark target, before the ones given after `--`
+    pub fn bench_args(&self, bench_target_name: &str) -> Vec<String> {
+        self.bench_target_config(bench_target_name)
+            .and_then(|config| config.bench_args.clone())
+            .or_else(|| self.bench_args.clone())
+            .unwrap_or_default()
+    }
+
+    fn bench_target_config(&self, bench_target_name: &str) -> Option<&BenchTargetConfig> {
+        self.benches.as_ref()?.get(bench_target_name)
+    }
+}
@@ -0,0 +1,175 @@
+use crate::prelude::*;
+use cargo_metadata::{Metadata, Package, PackageId};
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Computes a fingerprint of the sources of a package, its local dependencies and the lockfile
+///
+/// Only the inputs of cargo are considered: the manifests, and the directories of the targets of
+/// each package such as `src/` and `benches/`. For the targets at the root of a package, e.g.
+/// `build.rs`, only their source file is considered.
+///
+/// The fingerprint is based on the paths, sizes and modification times of the files, so that it
+/// is cheap to compute before every run. Hidden files and the target directory are ignored.
+pub fn source_fingerprint(metadata: &Metadata, package: &Package) -> Result<String> {
+    with_lockfile(metadata, sources_fingerprint(metadata, package)?)
+}
+
+/// Fingerprint of the sources of a package and its local dependencies, without the lockfile
+///
+/// When building, it is computed before running cargo so that the files edited during the build
+/// are not recorded as fresh, while the lockfile, which cargo may update, is added afterwards
+/// with [`with_lockfile`].
+pub fn sources_fingerprint(metadata: &Metadata, package: &Package) -> Result<Fnv1a> {
+    let mut files = BTreeMap::new();
+    let mut source_dirs = BTreeSet::new();
+    add_file(
+        metadata.workspace_root.join("Cargo.toml").as_std_path(),
+        &mut files,
+    )?;
+    for package in local_packages(metadata, package) {
+        let package_dir = package.manifest_path.parent().map(|dir| dir.as_std_path());
+        add_file(package.manifest_path.as_std_path(), &mut files)?;
+        for target in &package.targets {
+            let src_path = target.src_path.as_std_path();
+            match src_path.parent() {
+                Some(dir) if Some(dir) != package_dir => {
+                    source_dirs.insert(dir.to_path_buf());
+                }
+                _ => add_file(src_path, &mut files)?,
+            }
+        }
+    }
+
+    // A directory comes before its subdirectories, which are then already walked
+    let mut walked_dirs: Vec<&Path> = vec![];
+    for dir in &source_dirs {
+        if walked_dirs
+            .iter()
+            .any(|walked_dir| dir.starts_with(walked_dir))
+        {
+            continue;
+        }
+        collect_files(dir, metadata.target_directory.as_std_path(), &mut files)?;
+        walked_dirs.push(dir);
+    }
+
+    let mut hasher = Fnv1a::default();
+    for (path, stamp) in files {
+        hash_file(&mut hasher, &path, stamp);
+    }
+    Ok(hasher)
+}
+
+/// Completes a fingerprint of the sources with the lockfile of the workspace
+pub fn with_lockfile(metadata: &Metadata, mut hasher: Fnv1a) -> Result<String> {
+    let lockfile: PathBuf = metadata.workspace_root.join("Cargo.lock").into();
+    if lockfile.exists() {
+        let stamp = file_stamp(&lockfile)?;
+        hash_file(&mut hasher, &lockfile, stamp);
+    }
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+fn hash_file(hasher: &mut Fnv1a, path: &Path, (size, modified): (u64, u128)) {
+    hasher.write(path.to_string_lossy().as_bytes());
+    hasher.write(&[0]);
+    hasher.write(&size.to_le_bytes());
+    hasher.write(&modified.to_le_bytes());
+}
+
+/// The package and its transitive dependencies that are not from a registry or a git repository
+fn local_packages<'a>(metadata: &'a Metadata, package: &'a Package) -> Vec<&'a Package> {
+    let Some(resolve) = &metadata.resolve else {
+        return vec![package];
+    };
+
+    let mut visited: HashSet<&PackageId> = HashSet::new();
+    let mut pending = vec![&package.id];
+    let mut packages = vec![];
+    while let Some(id) = pending.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        let Some(package) = metadata.packages.iter().find(|p| &p.id == id) else {
+            continue;
+        };
+        if package.source.is_some() {
+            continue;
+        }
+        packages.push(package);
+        if let Some(node) = resolve.nodes.iter().find(|node| &node.id == id) {
+            pending.extend(node.deps.iter().map(|dep| &dep.pkg));
+        }
+    }
+    packages
+}
+
+/// Adds a file to the fingerprinted files, if it exists
+fn add_file(path: &Path, files: &mut BTreeMap<PathBuf, (u64, u128)>) -> Result<()> {
+    if let Entry::Vacant(entry) = files.entry(path.to_path_buf()) {
+        if path.is_file() {
+            entry.insert(file_stamp(path)?);
+        }
+    }
+    Ok(())
+}
+
+fn collect_files(
+    dir: &Path,
+    target_dir: &Path,
+    files: &mut BTreeMap<PathBuf, (u64, u128)>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if file_name.starts_with('.') || path == target_dir {
+            continue;
+        }
+        // Symlinked directories are not followed, as they could form a cycle
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&path, target_dir, files)?;
+        } else if file_type.is_file() || path.is_file() {
+            add_file(&path, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Size and modification time in nanoseconds of a file
+fn file_stamp(path: &Path) -> Result<(u64, u128)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    Ok((metadata.len(), modified))
+}
+
+/// 64-bit FNV-1a hash, unlike the std hashers its output is stable across Rust versions
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Fnv1a {
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -1,15 +1,55 @@
-use crate::{measurement_mode::BuildMode, prelude::*};
+use crate::{fingerprint::Fnv1a, measurement_mode::BuildMode, prelude::*};
 use cargo_metadata::Metadata;
-use std::path::{Path, PathBuf};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-pub fn get_codspeed_target_dir(metadata: &Metadata, build_mode: BuildMode) -> PathBuf {
+/// Directory of the built benchmarks, e.g. `target/codspeed/<mode>[/<triple>]/<profile>`
+pub fn get_codspeed_target_dir(
+    metadata: &Metadata,
+    build_mode: BuildMode,
+    target: Option<&str>,
+    profile: &str,
+) -> PathBuf {
+    let mut codspeed_target_dir = metadata
+        .target_directory
+        .join("codspeed")
+        .join(build_mode.to_string());
+    if let Some(target) = target {
+        codspeed_target_dir.push(target);
+    }
+    codspeed_target_dir.join(profile).into()
+}
+
+/// Cargo target directory used to build the benchmarks of a build mode, e.g.
+/// `target/codspeed/build/<mode>`
+///
+/// Each build mode uses different rust flags, so sharing a target directory would make cargo
+/// rebuild the whole dependency graph whenever the mode changes.
+pub fn get_cargo_build_dir(metadata: &Metadata, build_mode: BuildMode) -> PathBuf {
     metadata
         .target_directory
         .join("codspeed")
+        .join("build")
         .join(build_mode.to_string())
         .into()
 }
 
+/// Path of a named walltime baseline, e.g. `target/codspeed/baselines/<name>.json`
+pub fn get_baseline_path(metadata: &Metadata, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        bail!("Invalid baseline name `{name}`");
+    }
+    Ok(metadata
+        .target_directory
+        .join("codspeed")
+        .join("baselines")
+        .join(format!("{name}.json"))
+        .into())
+}
+
 pub fn clear_dir<P>(dir: P) -> Result<()>
 where
     P: AsRef<Path>,
@@ -25,3 +65,91 @@ where
     }
     Ok(())
 }
+
+/// Formats a duration in nanoseconds with a human readable unit, e.g. `1.23 µs`
+pub fn format_duration_ns(ns: f64) -> String {
+    const UNITS: [(f64, &str); 4] = [(1e9, "s"), (1e6, "ms"), (1e3, "µs"), (1.0, "ns")];
+    let (factor, unit) = UNITS
+        .into_iter()
+        .find(|(factor, _)| ns.abs() >= *factor)
+        .unwrap_or((1.0, "ns"));
+    format!("{:.2} {unit}", ns / factor)
+}
+
+/// Parses an environment variable assignment such as `RAYON_NUM_THREADS=1`
+pub fn parse_env_var(assignment: &str) -> Result<(String, String)> {
+    match assignment.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => bail!("Invalid environment variable `{assignment}`, expected `KEY=VALUE`"),
+    }
+}
+
+/// Splits a command, e.g. the `--wrapper` or a target runner, into its arguments, following the
+/// quoting rules of a POSIX shell
+pub fn split_command(kind: &str, command: &str) -> Result<Vec<String>> {
+    shlex::split(command).ok_or_else(|| anyhow!("Invalid {kind} `{command}`, unbalanced quotes"))
+}
+
+/// Parses a duration such as `90s`, `1.5m`, `500ms` or `2h`. Plain numbers are in seconds.
+pub fn parse_duration(duration: &str) -> Result<Duration> {
+    let duration = duration.trim();
+    let unit_start = duration
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(duration.len());
+    let (value, unit) = duration.split_at(unit_start);
+    let value: f64 = value
+        .parse()
+        .map_err(|_| anyhow!("Invalid duration `{duration}`"))?;
+    let seconds = match unit.trim() {
+        "ms" => value / 1e3,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        unit => bail!("Invalid duration unit `{unit}`, expected one of ms, s, m or h"),
+    };
+    if seconds <= 0.0 {
+        bail!("Duration `{duration}` must be greater than zero");
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| anyhow!("Invalid duration `{duration}`"))
+}
+
+/// Subset of the benchmark suites to run, e.g. `2/4` for the second of four shards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// Index of the shard, starting at 1
+    pub index: usize,
+    pub total: usize,
+}
+
+impl Shard {
+    /// Whether a benchmark suite belongs to this shard
+    ///
+    /// The assignment only depends on the hash of the suite, so that adding or removing suites
+    /// does not move the other ones to different shards.
+    pub fn contains(&self, package_name: &str, bench_target_name: &str) -> bool {
+        let mut hasher = Fnv1a::default();
+        hasher.write(package_name.as_bytes());
+        hasher.write(&[0]);
+        hasher.write(bench_target_name.as_bytes());
+        hasher.finish() % self.total as u64 == (self.index - 1) as u64
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.total)
+    }
+}
+
+/// Parses a shard such as `1/4`
+pub fn parse_shard(shard: &str) -> Result<Shard> {
+    let invalid_shard =
+        || anyhow!("Invalid shard `{shard}`, expected `<index>/<total>`, e.g. `1/4`");
+    let (index, total) = shard.split_once('/').ok_or_else(invalid_shard)?;
+    let index: usize = index.trim().parse().map_err(|_| invalid_shard())?;
+    let total: usize = total.trim().parse().map_err(|_| invalid_shard())?;
+    if index == 0 || index > total {
+        bail!("Invalid shard `{shard}`, the index must be between 1 and {total}");
+    }
+    Ok(Shard { index, total })
+}
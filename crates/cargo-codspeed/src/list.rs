@@ -0,0 +1,98 @@
+use crate::{
+    app::{BenchNameFilters, BenchTargetFilters, PackageFilters},
+    config::CodSpeedConfig,
+    helpers::get_codspeed_target_dir,
+    measurement_mode::{BuildMode, MeasurementMode},
+    prelude::*,
+    run::no_benchmarks_found,
+    runner::get_target_runner,
+};
+use cargo_metadata::Metadata;
+
+/// Suffix of the lines printed by the compat harnesses when run with `--list`
+const LIST_LINE_SUFFIX: &str = ": benchmark";
+
+pub struct ListConfig {
+    pub bench_name_filter: Option<String>,
+    pub bench_name_filters: BenchNameFilters,
+    pub package_filters: PackageFilters,
+    pub bench_target_filters: BenchTargetFilters,
+    pub measurement_mode: MeasurementMode,
+    pub workspace_config: CodSpeedConfig,
+    pub target: Option<String>,
+    pub profile: String,
+}
+
+/// Prints the URI of every benchmark that `cargo codspeed run` would execute, one per line.
+///
+/// Benchmarks are discovered by running the previously built binaries with `--list`, which the
+/// compat harnesses handle by printing the URIs instead of running the benchmarks.
+pub fn list_benches(metadata: &Metadata, config: ListConfig) -> Result<()> {
+    let codspeed_target_dir = get_codspeed_target_dir(
+        metadata,
+        config.measurement_mode.into(),
+        config.target.as_deref(),
+        &config.profile,
+    );
+    let runner = match &config.target {
+        Some(target) => get_target_runner(target)?.unwrap_or_default(),
+        None => vec![],
+    };
+    let benches = config.package_filters.benches_to_run(
+        metadata,
+        &config.bench_target_filters,
+        &config.workspace_config,
+        codspeed_target_dir,
+    )?;
+    if benches.is_empty() {
+        return Err(no_benchmarks_found(
+            metadata,
+            config.measurement_mode,
+            config.target.as_deref(),
+            &config.profile,
+        ));
+    }
+
+    let mut benchmark_count = 0;
+    for bench in benches.iter() {
+        let mut command = bench.command(metadata, &runner);
+        command.arg("--list");
+        // The walltime harnesses only list the URIs when run by CodSpeed
+        if BuildMode::from(config.measurement_mode) == BuildMode::Walltime
+            && std::env::var_os("CODSPEED_ENV").is_none()
+        {
+            command.env("CODSPEED_ENV", "local");
+        }
+        if let Some(bench_name_filter) = config.bench_name_filter.as_ref() {
+            command.arg(bench_name_filter);
+        }
+        config.bench_name_filters.add_bench_args(&mut command);
+
+        let output = command
+            .output()
+            .map_err(|e| anyhow!("failed to execute the benchmark process: {e}"))?;
+        if !output.status.success() {
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+            bail!(
+                "failed to list the benchmarks of {} {}: {}",
+                bench.package_name,
+                bench.bench_target_name,
+                output.status
+            );
+        }
+
+        for uri in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.strip_suffix(LIST_LINE_SUFFIX))
+        {
+            println!("{uri}");
+            benchmark_count += 1;
+        }
+    }
+    eprintln!(
+        "Found {benchmark_count} benchmark(s) in {} benchmark suite(s)",
+        benches.len()
+    );
+
+    Ok(())
+}
@@ -1,9 +1,19 @@
 mod app;
 mod build;
+mod changes;
+mod compare;
+mod config;
+mod fingerprint;
 mod helpers;
+mod list;
+mod manifest;
 mod measurement_mode;
+mod messages;
 mod prelude;
+#[cfg(unix)]
+mod process_group;
 mod run;
+mod runner;
 
 use crate::prelude::*;
 use std::{env::args_os, process::exit};
@@ -0,0 +1,134 @@
+use crate::{config::CodSpeedConfig, prelude::*};
+use anyhow::Context;
+use cargo_metadata::Metadata;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Packages whose major version must match the one of cargo-codspeed
+const COMPAT_PACKAGES: [&str; 4] = [
+    "codspeed",
+    "codspeed-bencher-compat",
+    "codspeed-criterion-compat",
+    "codspeed-divan-compat",
+];
+
+/// Description of the benchmarks built by `cargo codspeed build`, stored next to them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// Version of cargo-codspeed that built the benchmarks
+    pub cargo_codspeed_version: String,
+    pub profile: String,
+    pub target: Option<String>,
+    /// Versions of the CodSpeed crates used by the benchmarks, by package name
+    pub compat_versions: BTreeMap<String, String>,
+    pub benches: Vec<ManifestBench>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestBench {
+    pub package: String,
+    pub bench: String,
+    /// Path of the executable, relative to the manifest
+    pub executable_path: PathBuf,
+    pub features: FeatureSelection,
+    pub rustflags: Vec<String>,
+    /// Fingerprint of the sources the executable was built from, see [`source_fingerprint`]
+    ///
+    /// [`source_fingerprint`]: crate::fingerprint::source_fingerprint
+    #[serde(default)]
+    pub source_fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureSelection {
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+}
+
+impl BuildManifest {
+    pub fn new(metadata: &Metadata, profile: &str, target: Option<&str>) -> Self {
+        let compat_versions = metadata
+            .packages
+            .iter()
+            .filter(|package| COMPAT_PACKAGES.contains(&package.name.as_str()))
+            .map(|package| (package.name.to_string(), package.version.to_string()))
+            .collect();
+        Self {
+            cargo_codspeed_version: env!("CARGO_PKG_VERSION").to_string(),
+            profile: profile.to_string(),
+            target: target.map(String::from),
+            compat_versions,
+            benches: vec![],
+        }
+    }
+
+    /// Whether a codspeed target directory has a manifest
+    pub fn exists(codspeed_target_dir: &Path) -> bool {
+        codspeed_target_dir.join(MANIFEST_FILE_NAME).exists()
+    }
+
+    /// Reads the manifest of a codspeed target directory, if any
+    pub fn read(codspeed_target_dir: &Path) -> Result<Option<Self>> {
+        if !Self::exists(codspeed_target_dir) {
+            return Ok(None);
+        }
+        let path = codspeed_target_dir.join(MANIFEST_FILE_NAME);
+        let file = std::fs::File::open(&path)
+            .with_context(|| format!("Failed to open build manifest at {}", path.display()))?;
+        let manifest = serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("Failed to parse build manifest at {}", path.display()))?;
+        Ok(Some(manifest))
+    }
+
+    pub fn write(&self, codspeed_target_dir: &Path) -> Result<()> {
+        let path = codspeed_target_dir.join(MANIFEST_FILE_NAME);
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create build manifest at {}", path.display()))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// Warns about the builds that are likely to fail or to produce unexpected results with this
+    /// version of cargo-codspeed and the current configuration
+    pub fn warn_if_stale(&self, workspace_config: &CodSpeedConfig) {
+        let version = env!("CARGO_PKG_VERSION");
+        if major_version(&self.cargo_codspeed_version) != major_version(version) {
+            eprintln!(
+                "Warning: The benchmarks were built by cargo-codspeed v{}, but this is v{version}. Rebuild them with `cargo codspeed build`.",
+                self.cargo_codspeed_version
+            );
+        }
+        for (package, compat_version) in &self.compat_versions {
+            if major_version(compat_version) != major_version(version) {
+                eprintln!(
+                    "Warning: The benchmarks depend on {package} v{compat_version}, which is not compatible with cargo-codspeed v{version}. \
+                    Use the same major version for both."
+                );
+            }
+        }
+
+        if let Some(features) = &workspace_config.features {
+            for bench in &self.benches {
+                if !bench.features.all_features && &bench.features.features != features {
+                    eprintln!(
+                        "Warning: `{}` in package `{}` was built with features [{}], but the configuration sets [{}]",
+                        bench.bench,
+                        bench.package,
+                        bench.features.features.join(", "),
+                        features.join(", ")
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn major_version(version: &str) -> &str {
+    version.split('.').next().unwrap_or(version)
+}
@@ -1,5 +1,5 @@
 use clap::ValueEnum;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -22,11 +22,12 @@ impl fmt::Display for BuildMode {
     }
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum, Serialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, ValueEnum, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum MeasurementMode {
     #[default]
     #[value(alias = "instrumentation")]
+    #[serde(alias = "instrumentation")]
     Simulation,
     Walltime,
     Memory,
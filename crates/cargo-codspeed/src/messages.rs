@@ -0,0 +1,73 @@
+use crate::measurement_mode::MeasurementMode;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    /// Human-readable output
+    #[default]
+    Human,
+    /// One JSON message per line on stdout, human-readable output is moved to stderr
+    Json,
+}
+
+/// Number of benchmarks reported by the benchmark processes, by action
+#[derive(Debug, Default, Serialize)]
+pub struct BenchmarkCounts {
+    pub measured: usize,
+    pub checked: usize,
+    pub skipped: usize,
+}
+
+impl BenchmarkCounts {
+    /// Counts a message forwarded from a benchmark process, based on its `reason`
+    pub fn record(&mut self, reason: &str) {
+        match reason {
+            "benchmark-measured" => self.measured += 1,
+            "benchmark-checked" => self.checked += 1,
+            "benchmark-skipped" => self.skipped += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Messages emitted by `cargo codspeed run --message-format json`
+///
+/// Benchmark messages (`benchmark-measured`, `benchmark-checked` and `benchmark-skipped`) are
+/// emitted by the benchmark processes themselves, and forwarded with the suite they belong to.
+/// The walltime harnesses do not emit them, so `benchmark-measured` is emitted for each walltime
+/// benchmark once the results of all the suites are collected.
+#[derive(Debug, Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message<'a> {
+    BenchmarkMeasured {
+        uri: &'a str,
+        name: &'a str,
+    },
+    SuiteStarted {
+        package: &'a str,
+        bench_target: &'a str,
+    },
+    SuiteFinished {
+        package: &'a str,
+        bench_target: &'a str,
+        success: bool,
+        exit_code: Option<i32>,
+        timed_out: bool,
+    },
+    RunFinished {
+        measurement_mode: MeasurementMode,
+        suites: usize,
+        failed_suites: usize,
+        benchmarks: &'a BenchmarkCounts,
+    },
+}
+
+impl Message<'_> {
+    pub fn emit(&self) {
+        println!(
+            "{}",
+            serde_json::to_string(self).expect("Failed to serialize message")
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Process groups of the benchmark suites run with a timeout
+//!
+//! These suites are spawned in their own process group, so that the processes started by a
+//! wrapper or a target runner can be killed along with the benchmark. Processes out of the
+//! foreground group do not receive the Ctrl-C of the terminal though, so SIGINT and SIGTERM are
+//! forwarded to the tracked groups before cargo-codspeed itself terminates.
+use std::{
+    io,
+    os::unix::process::CommandExt,
+    process::{Child, Command},
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Once,
+    },
+};
+
+/// Maximum number of groups tracked at once, the suites spawned beyond it stay in the group of
+/// cargo-codspeed
+const MAX_TRACKED_GROUPS: usize = 256;
+
+/// IDs of the tracked groups, `0` for a free slot and `-1` for a slot reserved while spawning.
+/// Only atomics are used, since they are read from the signal handler.
+static TRACKED_GROUPS: [AtomicI32; MAX_TRACKED_GROUPS] =
+    [const { AtomicI32::new(0) }; MAX_TRACKED_GROUPS];
+
+static INSTALL_SIGNAL_HANDLERS: Once = Once::new();
+
+/// Process group tracked until dropped
+pub struct TrackedGroup {
+    slot: &'static AtomicI32,
+}
+
+impl Drop for TrackedGroup {
+    fn drop(&mut self) {
+        self.slot.store(0, Ordering::SeqCst);
+    }
+}
+
+/// Spawns the command in its own process group, tracked until the returned group is dropped
+///
+/// The command is spawned in the group of cargo-codspeed if too many groups are already tracked.
+pub fn spawn_in_own_group(command: &mut Command) -> io::Result<(Child, Option<TrackedGroup>)> {
+    INSTALL_SIGNAL_HANDLERS.call_once(install_signal_handlers);
+    let Some(slot) = TRACKED_GROUPS.iter().find(|slot| {
+        slot.compare_exchange(0, -1, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }) else {
+        return command.spawn().map(|child| (child, None));
+    };
+    // Frees the slot if spawning fails
+    let group = TrackedGroup { slot };
+    let child = command.process_group(0).spawn()?;
+    slot.store(child.id() as i32, Ordering::SeqCst);
+    Ok((child, Some(group)))
+}
+
+fn install_signal_handlers() {
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        unsafe {
+            let mut previous_action: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(signal, std::ptr::null(), &mut previous_action);
+            // Keep ignoring the signals ignored by the parent, e.g. with `nohup`
+            if previous_action.sa_sigaction == libc::SIG_IGN {
+                continue;
+            }
+
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = forward_signal as extern "C" fn(libc::c_int) as usize;
+            action.sa_flags = libc::SA_RESETHAND;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+extern "C" fn forward_signal(signal: libc::c_int) {
+    for group in &TRACKED_GROUPS {
+        let process_group = group.load(Ordering::SeqCst);
+        if process_group > 0 {
+            unsafe { libc::kill(-process_group, signal) };
+        }
+    }
+    // The default action was restored by `SA_RESETHAND`, and applies once the handler returns
+    unsafe { libc::raise(signal) };
+}
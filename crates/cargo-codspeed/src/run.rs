@@ -1,25 +1,86 @@
 use crate::{
-    app::{BenchTargetFilters, PackageFilters},
-    helpers::get_codspeed_target_dir,
+    app::{BenchNameFilters, BenchTargetFilters, PackageFilters},
+    changes::packages_affected_since,
+    compare::{print_comparison, read_walltime_results, CompareConfig},
+    config::CodSpeedConfig,
+    fingerprint::source_fingerprint,
+    helpers::{get_baseline_path, get_codspeed_target_dir, Shard},
+    manifest::BuildManifest,
     measurement_mode::{BuildMode, MeasurementMode},
+    messages::{BenchmarkCounts, Message, MessageFormat},
     prelude::*,
+    runner::get_target_runner,
 };
 use anyhow::Context;
 use cargo_metadata::{Metadata, Package};
 use codspeed::walltime_results::WalltimeResults;
 use std::{
-    io::{self, Write},
+    collections::{BTreeMap, HashMap},
+    io::{self, BufRead, BufReader, Read, Write},
     path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
 };
+use wait_timeout::ChildExt;
 
+#[cfg(unix)]
+use crate::process_group::{spawn_in_own_group, TrackedGroup};
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
 
-struct BenchToRun {
-    bench_path: PathBuf,
-    bench_target_name: String,
-    working_directory: PathBuf,
-    package_name: String,
+pub(crate) struct BenchToRun {
+    pub(crate) bench_path: PathBuf,
+    pub(crate) bench_target_name: String,
+    pub(crate) working_directory: PathBuf,
+    pub(crate) package_name: String,
+    /// Timeout set in the `[package.metadata.codspeed]` table of the package
+    pub(crate) timeout: Option<Duration>,
+    /// Arguments from the `bench-args` setting, forwarded before the ones of the command line
+    pub(crate) bench_args: Vec<String>,
+    /// Environment variables from the configuration of the package and of the target
+    pub(crate) env: BTreeMap<String, String>,
+    /// Fingerprint of the sources at build time, if recorded
+    pub(crate) source_fingerprint: Option<String>,
+}
+
+impl BenchToRun {
+    /// Creates the command running the benchmark binary, with the environment expected by the
+    /// compat layers
+    ///
+    /// The binary is executed through the `runner` if not empty, e.g. `qemu-aarch64 -L /usr/aarch64-linux-gnu`.
+    pub(crate) fn command(&self, metadata: &Metadata, runner: &[String]) -> Command {
+        let mut command = match runner.split_first() {
+            Some((program, args)) => {
+                let mut command = Command::new(program);
+                command.args(args).arg(&self.bench_path);
+                command
+            }
+            None => Command::new(&self.bench_path),
+        };
+        // workspace_root is needed since file! returns the path relatively to the workspace root
+        // while CARGO_MANIFEST_DIR returns the path to the sub package
+        command
+            .env("CODSPEED_CARGO_WORKSPACE_ROOT", &metadata.workspace_root)
+            .env("CODSPEED_CARGO_TARGET_DIR", &metadata.target_directory)
+            .current_dir(&self.working_directory);
+        command
+    }
+}
+
+impl BenchNameFilters {
+    /// Adds the `--skip` and `--exact` arguments understood by all the compat harnesses
+    pub(crate) fn add_bench_args(&self, command: &mut Command) {
+        for pattern in &self.skip {
+            command.arg("--skip").arg(pattern);
+        }
+        if self.exact {
+            command.arg("--exact");
+        }
+    }
 }
 
 impl PackageFilters {
@@ -47,45 +108,58 @@ impl PackageFilters {
         )
     }
 
-    fn benches_to_run(
+    pub(crate) fn benches_to_run(
         &self,
         metadata: &Metadata,
-        bench_target_filters: BenchTargetFilters,
+        bench_target_filters: &BenchTargetFilters,
+        workspace_config: &CodSpeedConfig,
         codspeed_target_dir: PathBuf,
     ) -> Result<Vec<BenchToRun>> {
         let packages = self.packages_from_flags(metadata)?;
+        let Some(manifest) = BuildManifest::read(&codspeed_target_dir)? else {
+            return Ok(vec![]);
+        };
+        manifest.warn_if_stale(workspace_config);
 
         let mut benches = vec![];
         for package in packages {
             let package_name = &package.name;
-            let package_target_dir = codspeed_target_dir.join(package_name.to_string());
             let working_directory = package.manifest_path.parent().ok_or_else(|| {
                 Error::msg(format!("Failed to get root dir for package {package_name}"))
             })?;
-            if let io::Result::Ok(read_dir) = std::fs::read_dir(&package_target_dir) {
-                for entry in read_dir {
-                    let entry = entry?;
-                    let bench_path = entry.path();
-                    let bench_target_name = bench_path
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                        .to_string();
-                    if let Some(bench_target_filter) = &bench_target_filters.bench {
-                        if !bench_target_filter.contains(&bench_target_name) {
-                            continue;
-                        }
-                    }
-                    if !bench_path.is_dir() {
-                        benches.push(BenchToRun {
-                            package_name: package_name.to_string(),
-                            working_directory: working_directory.into(),
-                            bench_path,
-                            bench_target_name,
-                        });
+            let package_config = CodSpeedConfig::for_package(package)?;
+            let config = package_config.or(workspace_config.clone());
+            for built_bench in manifest
+                .benches
+                .iter()
+                .filter(|bench| bench.package == package_name.as_str())
+            {
+                let bench_target_name = &built_bench.bench;
+                if let Some(bench_target_filter) = &bench_target_filters.bench {
+                    if !bench_target_filter.contains(bench_target_name) {
+                        continue;
                     }
+                } else if config.excludes_bench(bench_target_name) {
+                    continue;
                 }
+                let bench_path = codspeed_target_dir.join(&built_bench.executable_path);
+                if !bench_path.exists() {
+                    eprintln!(
+                        "Warning: Missing executable for `{bench_target_name}` in package `{package_name}` at {}",
+                        bench_path.display()
+                    );
+                    continue;
+                }
+                benches.push(BenchToRun {
+                    package_name: package_name.to_string(),
+                    working_directory: working_directory.into(),
+                    bench_path,
+                    bench_target_name: bench_target_name.clone(),
+                    timeout: config.timeout,
+                    bench_args: config.bench_args(bench_target_name),
+                    env: config.bench_env(bench_target_name),
+                    source_fingerprint: built_bench.source_fingerprint.clone(),
+                });
             }
         }
 
@@ -93,86 +167,726 @@ impl PackageFilters {
     }
 }
 
-pub fn run_benches(
+#[derive(Clone)]
+pub struct RunConfig {
+    pub bench_name_filter: Option<String>,
+    pub bench_name_filters: BenchNameFilters,
+    pub package_filters: PackageFilters,
+    pub bench_target_filters: BenchTargetFilters,
+    pub measurement_mode: MeasurementMode,
+    pub bench_args: Vec<String>,
+    pub save_baseline: Option<String>,
+    pub baseline: Option<String>,
+    pub keep_raw_samples: bool,
+    pub message_format: MessageFormat,
+    pub jobs: usize,
+    pub no_fail_fast: bool,
+    /// `--timeout`, over the configured timeout of each suite
+    pub timeout: Option<Duration>,
+    pub workspace_config: CodSpeedConfig,
+    pub target: Option<String>,
+    pub profile: String,
+    pub require_fresh: bool,
+    /// Command prefixing each benchmark invocation, before the target runner if any
+    pub wrapper: Vec<String>,
+    /// Environment variables set for every benchmark suite, over the configured ones
+    pub env: Vec<(String, String)>,
+    /// Number of times each suite is run, the walltime results of the runs being merged
+    pub repeat: usize,
+    /// Run all the suites before repeating them, rather than repeating each suite in a row
+    pub interleave: bool,
+    /// Only run the suites of the packages affected by the changes since this git reference
+    pub changed_since: Option<String>,
+    /// Only run the suites of this shard
+    pub shard: Option<Shard>,
+}
+
+impl RunConfig {
+    /// Configuration of a measurement mode, without the walltime-only options for the other modes
+    fn for_mode(&self, measurement_mode: MeasurementMode) -> RunConfig {
+        let mut config = RunConfig {
+            measurement_mode,
+            ..self.clone()
+        };
+        if BuildMode::from(measurement_mode) != BuildMode::Walltime {
+            config.save_baseline = None;
+            config.baseline = None;
+            config.repeat = 1;
+            config.interleave = false;
+        }
+        config
+    }
+
+    /// Timeout of a suite, `--timeout` taking precedence over the configured one
+    fn suite_timeout(&self, bench: &BenchToRun) -> Option<Duration> {
+        self.timeout.or(bench.timeout)
+    }
+}
+
+/// Runs the benchmarks of each measurement mode in turn, each mode producing its own results
+///
+/// With `--no-fail-fast`, the next modes are run even if some suites failed.
+pub fn run_benches_for_modes(
     metadata: &Metadata,
-    bench_name_filter: Option<String>,
-    package_filters: PackageFilters,
-    bench_target_filters: BenchTargetFilters,
-    measurement_mode: MeasurementMode,
-    bench_args: Vec<String>,
+    config: RunConfig,
+    measurement_modes: &[MeasurementMode],
 ) -> Result<()> {
+    // Fail before running anything if an option does not apply to any of the modes
+    check_mode_options(&config, measurement_modes)?;
+
+    let mut first_failure = None;
+    for &measurement_mode in measurement_modes {
+        eprintln!("[cargo-codspeed] Measurement mode: {measurement_mode:?}\n");
+        let failure = run_benches(metadata, config.for_mode(measurement_mode))?;
+        if let Some(outcome) = failure {
+            if !config.no_fail_fast {
+                return exit_with_failure(outcome);
+            }
+            first_failure.get_or_insert(outcome);
+        }
+    }
+    match first_failure {
+        Some(outcome) => exit_with_failure(outcome),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the walltime-only options apply to one of the modes
+fn check_mode_options(config: &RunConfig, measurement_modes: &[MeasurementMode]) -> Result<()> {
+    if measurement_modes
+        .iter()
+        .any(|&mode| BuildMode::from(mode) == BuildMode::Walltime)
+    {
+        return Ok(());
+    }
+    if config.save_baseline.is_some() || config.baseline.is_some() {
+        bail!("--save-baseline and --baseline are only supported in walltime mode");
+    }
+    if config.repeat > 1 {
+        bail!("--repeat is only supported in walltime mode");
+    }
+    Ok(())
+}
+
+/// Error returned when no benchmark was built for a measurement mode, target and profile
+pub(crate) fn no_benchmarks_found(
+    metadata: &Metadata,
+    measurement_mode: MeasurementMode,
+    target: Option<&str>,
+    profile: &str,
+) -> Error {
+    let mut build_flags = format!("-m {measurement_mode}");
+    if let Some(target) = target {
+        build_flags.push_str(&format!(" --target {target}"));
+    }
+    if profile != "bench" {
+        build_flags.push_str(&format!(" --profile {profile}"));
+    }
+    let build_mode = BuildMode::from(measurement_mode);
+    let codspeed_target_dir = get_codspeed_target_dir(metadata, build_mode, target, profile);
+    if !BuildManifest::exists(&codspeed_target_dir) {
+        if let Some(legacy_dir) = legacy_build_dir(metadata, build_mode) {
+            return anyhow!(
+                "The benchmarks in {} were built by an older version of cargo-codspeed, which stored them without a build manifest. Run `cargo codspeed build {build_flags}` to rebuild them.",
+                legacy_dir.display()
+            );
+        }
+    }
+    anyhow!("No benchmarks found for the {measurement_mode} mode. Run `cargo codspeed build {build_flags}` first.")
+}
+
+/// Directory of the benchmarks of a workspace package built before the build manifest was
+/// introduced, when they were stored in `target/codspeed/<mode>/<package>`
+fn legacy_build_dir(metadata: &Metadata, build_mode: BuildMode) -> Option<PathBuf> {
+    let mode_dir = metadata
+        .target_directory
+        .join("codspeed")
+        .join(build_mode.to_string());
+    metadata
+        .workspace_packages()
+        .into_iter()
+        .map(|package| mode_dir.join(package.name.as_str()).into_std_path_buf())
+        .find(|package_dir| package_dir.is_dir())
+}
+
+/// Runs the benchmarks of a measurement mode
+///
+/// Returns the outcome of the first failed suite, if any.
+fn run_benches(metadata: &Metadata, config: RunConfig) -> Result<Option<SuiteOutcome>> {
+    let measurement_mode = config.measurement_mode;
     let build_mode = measurement_mode.into();
-    let codspeed_target_dir = get_codspeed_target_dir(metadata, build_mode);
-    let workspace_root = metadata.workspace_root.as_std_path();
+    // Fail early on a missing baseline rather than after running all the benchmarks
+    let baseline = match &config.baseline {
+        Some(name) => Some((
+            name,
+            read_walltime_results(&get_baseline_path(metadata, name)?)?,
+        )),
+        None => None,
+    };
+
+    let codspeed_target_dir = get_codspeed_target_dir(
+        metadata,
+        build_mode,
+        config.target.as_deref(),
+        &config.profile,
+    );
+    let mut runner = config.wrapper.clone();
+    if let Some(target) = &config.target {
+        runner.extend(get_target_runner(target)?.unwrap_or_default());
+    }
+    let target_dir = metadata.target_directory.as_std_path();
     if build_mode == BuildMode::Walltime {
-        WalltimeResults::clear(workspace_root)?;
+        WalltimeResults::clear(target_dir)?;
     }
-    let benches =
-        package_filters.benches_to_run(metadata, bench_target_filters, codspeed_target_dir)?;
+    let mut benches = config.package_filters.benches_to_run(
+        metadata,
+        &config.bench_target_filters,
+        &config.workspace_config,
+        codspeed_target_dir,
+    )?;
     if benches.is_empty() {
-        bail!("No benchmarks found for the {measurement_mode} mode. Run `cargo codspeed build -m {measurement_mode}` first.");
+        return Err(no_benchmarks_found(
+            metadata,
+            measurement_mode,
+            config.target.as_deref(),
+            &config.profile,
+        ));
+    }
+
+    if let Some(git_ref) = &config.changed_since {
+        let affected_packages = packages_affected_since(metadata, git_ref)?;
+        let total_suites = benches.len();
+        benches.retain(|bench| affected_packages.contains(&bench.package_name));
+        eprintln!(
+            "Selected {} of the {total_suites} benchmark suite(s) affected by the changes since `{git_ref}`",
+            benches.len()
+        );
     }
 
+    if let Some(shard) = config.shard {
+        let total_suites = benches.len();
+        benches.retain(|bench| shard.contains(&bench.package_name, &bench.bench_target_name));
+        eprintln!(
+            "Selected {} of the {total_suites} benchmark suite(s) for shard {shard}",
+            benches.len()
+        );
+    }
+
+    check_freshness(metadata, &benches, config.require_fresh)?;
+
     eprintln!("Collected {} benchmark suite(s) to run", benches.len());
 
-    for bench in benches.iter() {
-        let bench_target_name = &bench.bench_target_name;
-        // workspace_root is needed since file! returns the path relatively to the workspace root
-        // while CARGO_MANIFEST_DIR returns the path to the sub package
-        let workspace_root = metadata.workspace_root.clone();
-        eprintln!("Running {} {}", &bench.package_name, bench_target_name);
-        let mut command = std::process::Command::new(&bench.bench_path);
-        command
-            .env("CODSPEED_CARGO_WORKSPACE_ROOT", workspace_root)
-            .current_dir(&bench.working_directory);
+    let jobs = if config.jobs > 1 && build_mode == BuildMode::Walltime {
+        eprintln!("Warning: --jobs is ignored in walltime mode, benchmark suites are run sequentially to avoid measurement noise");
+        1
+    } else {
+        config.jobs
+    };
 
-        if build_mode == BuildMode::Walltime {
-            command.arg("--bench"); // Walltime targets need this additional argument (inherited from running them with `cargo bench`)
+    let mut benchmark_counts = BenchmarkCounts::default();
+    let failures = if jobs > 1 {
+        eprintln!("Running up to {jobs} benchmark suites in parallel");
+        run_suites_in_parallel(
+            metadata,
+            &config,
+            &runner,
+            &benches,
+            jobs,
+            &mut benchmark_counts,
+        )?
+    } else {
+        let suites = if config.interleave {
+            (0..config.repeat).flat_map(|_| &benches).collect_vec()
+        } else {
+            benches
+                .iter()
+                .flat_map(|bench| std::iter::repeat_n(bench, config.repeat))
+                .collect_vec()
+        };
+        if config.repeat > 1 {
+            eprintln!("Running each benchmark suite {} times", config.repeat);
         }
+        run_suites_sequentially(metadata, &config, &runner, &suites, &mut benchmark_counts)?
+    };
+    let report_run_finished = |benchmark_counts: &BenchmarkCounts| {
+        if config.message_format == MessageFormat::Json {
+            Message::RunFinished {
+                measurement_mode,
+                suites: benches.len(),
+                failed_suites: failures.len(),
+                benchmarks: benchmark_counts,
+            }
+            .emit();
+        }
+    };
+    if !config.no_fail_fast {
+        if let Some((_, outcome)) = failures.first() {
+            report_run_finished(&benchmark_counts);
+            return Ok(Some(*outcome));
+        }
+    }
+    eprintln!("Finished running {} benchmark suite(s)", benches.len());
 
-        if let Some(bench_name_filter) = bench_name_filter.as_ref() {
-            command.arg(bench_name_filter);
+    if build_mode == BuildMode::Walltime {
+        if let Some(results) =
+            aggregate_raw_walltime_data(target_dir, &config, &mut benchmark_counts)?
+        {
+            let baseline = baseline.as_ref().map(|(name, b)| (name.as_str(), b));
+            save_walltime_results(metadata, &config, baseline, &results)?;
         }
+    }
 
-        command.args(&bench_args);
+    report_run_finished(&benchmark_counts);
 
-        command
-            .status()
-            .map_err(|e| anyhow!("failed to execute the benchmark process: {e}"))
-            .and_then(|status| {
-                if status.success() {
-                    Ok(())
-                } else {
-                    #[cfg(unix)]
-                    {
-                        let code = status
-                            .code()
-                            .or(status.signal().map(|s| 128 + s)) // 128+N indicates that a command was interrupted by signal N (see: https://tldp.org/LDP/abs/html/exitcodes.html)
-                            .unwrap_or(1);
-
-                        eprintln!("failed to execute the benchmark process, exit code: {code}");
-
-                        std::process::exit(code);
-                    }
-                    #[cfg(not(unix))]
-                    {
-                        bail!("failed to execute the benchmark process: {}", status)
-                    }
+    if let Some((_, outcome)) = failures.first() {
+        eprintln!("\n{} benchmark suite(s) failed:", failures.len());
+        for (bench, outcome) in &failures {
+            eprintln!(
+                "  {} {}: {}",
+                bench.package_name,
+                bench.bench_target_name,
+                outcome.describe()
+            );
+        }
+        return Ok(Some(*outcome));
+    }
+
+    Ok(None)
+}
+
+/// Warns about the benchmarks built from sources that changed since, or fails if fresh builds are
+/// required
+fn check_freshness(metadata: &Metadata, benches: &[BenchToRun], require_fresh: bool) -> Result<()> {
+    let mut current_fingerprints = HashMap::new();
+    let mut stale_benches = vec![];
+    for bench in benches {
+        if !current_fingerprints.contains_key(&bench.package_name) {
+            let fingerprint = metadata
+                .workspace_packages()
+                .into_iter()
+                .find(|package| package.name.as_str() == bench.package_name)
+                .map(|package| source_fingerprint(metadata, package))
+                .transpose()?;
+            current_fingerprints.insert(bench.package_name.clone(), fingerprint);
+        }
+        // Benchmarks built without a fingerprint are considered as stale
+        if bench.source_fingerprint.is_none()
+            || bench.source_fingerprint != current_fingerprints[&bench.package_name]
+        {
+            stale_benches.push(bench);
+        }
+    }
+    if stale_benches.is_empty() {
+        return Ok(());
+    }
+
+    let stale_list = stale_benches
+        .iter()
+        .map(|bench| {
+            format!(
+                "  - `{}` in package `{}`",
+                bench.bench_target_name, bench.package_name
+            )
+        })
+        .join("\n");
+    if require_fresh {
+        bail!(
+            "\
+The following benchmarks were built from sources that changed since:
+{stale_list}
+
+Rebuild them with `cargo codspeed build`."
+        );
+    }
+    eprintln!("\
+Warning: The following benchmarks were built from sources that changed since:
+{stale_list}
+
+Rebuild them with `cargo codspeed build`, or use `--require-fresh` to refuse running stale benchmarks.
+");
+    Ok(())
+}
+
+/// Creates the command running a benchmark suite with the options of the run
+fn suite_command(
+    metadata: &Metadata,
+    config: &RunConfig,
+    runner: &[String],
+    bench: &BenchToRun,
+) -> Command {
+    let mut command = bench.command(metadata, runner);
+    command.envs(&bench.env);
+    command.envs(config.env.iter().cloned());
+
+    // The raw samples of every run are needed to merge them
+    if config.keep_raw_samples || config.repeat > 1 {
+        command.env("CODSPEED_KEEP_RAW_SAMPLES", "true");
+    }
+
+    if config.message_format == MessageFormat::Json {
+        command.env("CODSPEED_MESSAGE_FORMAT", "json");
+    }
+
+    if BuildMode::from(config.measurement_mode) == BuildMode::Walltime {
+        command.arg("--bench"); // Walltime targets need this additional argument (inherited from running them with `cargo bench`)
+    }
+
+    if let Some(bench_name_filter) = config.bench_name_filter.as_ref() {
+        command.arg(bench_name_filter);
+    }
+    config.bench_name_filters.add_bench_args(&mut command);
+
+    command.args(&bench.bench_args);
+    command.args(&config.bench_args);
+    command
+}
+
+/// How a benchmark process ended
+#[derive(Debug, Clone, Copy)]
+enum SuiteOutcome {
+    Exited(ExitStatus),
+    /// The process was killed after exceeding the timeout
+    TimedOut(Duration),
+}
+
+impl SuiteOutcome {
+    fn success(&self) -> bool {
+        matches!(self, SuiteOutcome::Exited(status) if status.success())
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            #[cfg(unix)]
+            SuiteOutcome::Exited(status) if status.signal().is_some() => {
+                format!("terminated by signal {}", status.signal().unwrap())
+            }
+            SuiteOutcome::Exited(status) => match status.code() {
+                Some(code) => format!("exit code {code}"),
+                None => status.to_string(),
+            },
+            SuiteOutcome::TimedOut(timeout) => format!("timed out after {timeout:?}"),
+        }
+    }
+}
+
+/// Suites that failed, with the outcome of their process
+type SuiteFailures<'a> = Vec<(&'a BenchToRun, SuiteOutcome)>;
+
+/// Process of a benchmark suite
+struct SuiteProcess {
+    child: Child,
+    /// Group of the process, tracked until the process is waited for
+    #[cfg(unix)]
+    _group: Option<TrackedGroup>,
+}
+
+/// Spawns the benchmark process
+///
+/// With a timeout, the process is spawned in its own process group so that the whole suite can
+/// be killed. Since it does not receive Ctrl-C anymore, the group is then tracked to forward it
+/// the signals interrupting cargo-codspeed.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn spawn_suite(command: &mut Command, timeout: Option<Duration>) -> io::Result<SuiteProcess> {
+    #[cfg(unix)]
+    if timeout.is_some() {
+        let (child, group) = spawn_in_own_group(command)?;
+        return io::Result::Ok(SuiteProcess {
+            child,
+            _group: group,
+        });
+    }
+    command.spawn().map(|child| SuiteProcess {
+        child,
+        #[cfg(unix)]
+        _group: None,
+    })
+}
+
+/// Waits for the benchmark process, killing it if it runs longer than the timeout
+fn wait_for_suite(child: &mut Child, timeout: Option<Duration>) -> io::Result<SuiteOutcome> {
+    let Some(timeout) = timeout else {
+        return child.wait().map(SuiteOutcome::Exited);
+    };
+    match child.wait_timeout(timeout)? {
+        Some(status) => io::Result::Ok(SuiteOutcome::Exited(status)),
+        None => {
+            kill_suite(child)?;
+            child.wait()?;
+            io::Result::Ok(SuiteOutcome::TimedOut(timeout))
+        }
+    }
+}
+
+/// Kills the benchmark process and the processes of its group, so that a benchmark started by a
+/// wrapper or a target runner is killed as well
+fn kill_suite(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        // A negative PID designates the process group, created when spawning the suite
+        let process_group = -(child.id() as libc::pid_t);
+        if unsafe { libc::kill(process_group, libc::SIGKILL) } == 0 {
+            return io::Result::Ok(());
+        }
+    }
+    child.kill()
+}
+
+/// Runs the suites one after the other, streaming their output
+///
+/// Stops at the first failure unless `--no-fail-fast` is set.
+fn run_suites_sequentially<'a>(
+    metadata: &Metadata,
+    config: &RunConfig,
+    runner: &[String],
+    benches: &[&'a BenchToRun],
+    benchmark_counts: &mut BenchmarkCounts,
+) -> Result<SuiteFailures<'a>> {
+    let mut failures = vec![];
+    for &bench in benches {
+        let mut command = suite_command(metadata, config, runner, bench);
+        report_suite_started(config, bench);
+        let timeout = config.suite_timeout(bench);
+        let outcome = match config.message_format {
+            MessageFormat::Human => spawn_suite(&mut command, timeout)
+                .and_then(|mut suite| wait_for_suite(&mut suite.child, timeout)),
+            MessageFormat::Json => {
+                run_with_json_messages(&mut command, bench, benchmark_counts, timeout)
+            }
+        }
+        .map_err(|e| anyhow!("failed to execute the benchmark process: {e}"))?;
+
+        report_suite_finished(config, bench, outcome);
+        if !outcome.success() {
+            failures.push((bench, outcome));
+            if !config.no_fail_fast {
+                break;
+            }
+        }
+    }
+    Ok(failures)
+}
+
+/// Runs up to `jobs` suites concurrently. The suites are reported as started when their process
+/// is spawned, while the output of each suite is buffered and printed at once when it finishes,
+/// so that the outputs of different suites are not interleaved.
+///
+/// Unless `--no-fail-fast` is set, no new suite is started after a failure.
+fn run_suites_in_parallel<'a>(
+    metadata: &Metadata,
+    config: &RunConfig,
+    runner: &[String],
+    benches: &'a [BenchToRun],
+    jobs: usize,
+    benchmark_counts: &mut BenchmarkCounts,
+) -> Result<SuiteFailures<'a>> {
+    let next_suite = AtomicUsize::new(0);
+    let failures = Mutex::new(vec![]);
+    // Also used to print the output of one suite at a time
+    let benchmark_counts = Mutex::new(benchmark_counts);
+
+    let run_worker = || -> Result<()> {
+        while config.no_fail_fast || failures.lock().unwrap().is_empty() {
+            let Some(bench) = benches.get(next_suite.fetch_add(1, Ordering::SeqCst)) else {
+                break;
+            };
+            let mut command = suite_command(metadata, config, runner, bench);
+            {
+                let _benchmark_counts = benchmark_counts.lock().unwrap();
+                report_suite_started(config, bench);
+            }
+            let (outcome, stdout, stderr) =
+                output_with_timeout(&mut command, config.suite_timeout(bench))
+                    .map_err(|e| anyhow!("failed to execute the benchmark process: {e}"))?;
+
+            let mut benchmark_counts = benchmark_counts.lock().unwrap();
+            match config.message_format {
+                MessageFormat::Human => io::stdout().write_all(&stdout)?,
+                MessageFormat::Json => {
+                    forward_json_messages(&stdout[..], bench, &mut benchmark_counts)?
                 }
-            })?;
-        eprintln!("Done running {bench_target_name}");
+            }
+            io::stderr().write_all(&stderr)?;
+            report_suite_finished(config, bench, outcome);
+
+            if !outcome.success() {
+                failures.lock().unwrap().push((bench, outcome));
+            }
+        }
+        Ok(())
+    };
+
+    std::thread::scope(|scope| {
+        (0..jobs.min(benches.len()))
+            .map(|_| scope.spawn(run_worker))
+            .collect_vec()
+            .into_iter()
+            .try_for_each(|worker| worker.join().expect("Benchmark worker panicked"))
+    })?;
+
+    Ok(failures.into_inner().unwrap())
+}
+
+/// Runs the benchmark process with its output captured, killing it if it runs longer than the
+/// timeout
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Option<Duration>,
+) -> io::Result<(SuiteOutcome, Vec<u8>, Vec<u8>)> {
+    let mut suite = spawn_suite(
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+        timeout,
+    )?;
+    let mut stdout_pipe = suite
+        .child
+        .stdout
+        .take()
+        .expect("Unable to get stdout for child process");
+    let mut stderr_pipe = suite
+        .child
+        .stderr
+        .take()
+        .expect("Unable to get stderr for child process");
+
+    // The pipes are read while waiting, so that the process never blocks on a full pipe
+    std::thread::scope(|scope| {
+        let stdout = scope.spawn(move || {
+            let mut stdout = vec![];
+            stdout_pipe.read_to_end(&mut stdout).map(|_| stdout)
+        });
+        let stderr = scope.spawn(move || {
+            let mut stderr = vec![];
+            stderr_pipe.read_to_end(&mut stderr).map(|_| stderr)
+        });
+        let outcome = wait_for_suite(&mut suite.child, timeout)?;
+        io::Result::Ok((
+            outcome,
+            stdout.join().expect("Failed to read stdout")?,
+            stderr.join().expect("Failed to read stderr")?,
+        ))
+    })
+}
+
+fn report_suite_started(config: &RunConfig, bench: &BenchToRun) {
+    eprintln!(
+        "Running {} {}",
+        &bench.package_name, &bench.bench_target_name
+    );
+    if config.message_format == MessageFormat::Json {
+        Message::SuiteStarted {
+            package: &bench.package_name,
+            bench_target: &bench.bench_target_name,
+        }
+        .emit();
     }
-    eprintln!("Finished running {} benchmark suite(s)", benches.len());
+}
 
-    if build_mode == BuildMode::Walltime {
-        aggregate_raw_walltime_data(workspace_root)?;
+fn report_suite_finished(config: &RunConfig, bench: &BenchToRun, outcome: SuiteOutcome) {
+    if config.message_format == MessageFormat::Json {
+        let exit_status = match outcome {
+            SuiteOutcome::Exited(status) => Some(status),
+            SuiteOutcome::TimedOut(_) => None,
+        };
+        Message::SuiteFinished {
+            package: &bench.package_name,
+            bench_target: &bench.bench_target_name,
+            success: outcome.success(),
+            exit_code: exit_status.and_then(|status| status.code()),
+            timed_out: matches!(outcome, SuiteOutcome::TimedOut(_)),
+        }
+        .emit();
     }
+    match outcome {
+        _ if outcome.success() => eprintln!("Done running {}", bench.bench_target_name),
+        SuiteOutcome::TimedOut(timeout) => eprintln!(
+            "Benchmark suite {} {} timed out after {timeout:?} and was killed",
+            bench.package_name, bench.bench_target_name
+        ),
+        SuiteOutcome::Exited(_) => {}
+    }
+}
+
+/// Exits with the exit code of the failed benchmark process
+fn exit_with_failure(outcome: SuiteOutcome) -> Result<()> {
+    let status = match outcome {
+        SuiteOutcome::Exited(status) => status,
+        // Same exit code as the `timeout` command
+        SuiteOutcome::TimedOut(_) => {
+            eprintln!(
+                "failed to execute the benchmark process: {}",
+                outcome.describe()
+            );
+            std::process::exit(124);
+        }
+    };
+    #[cfg(unix)]
+    {
+        let code = status
+            .code()
+            .or(status.signal().map(|s| 128 + s)) // 128+N indicates that a command was interrupted by signal N (see: https://tldp.org/LDP/abs/html/exitcodes.html)
+            .unwrap_or(1);
 
+        eprintln!("failed to execute the benchmark process, exit code: {code}");
+
+        std::process::exit(code);
+    }
+    #[cfg(not(unix))]
+    {
+        bail!("failed to execute the benchmark process: {}", status)
+    }
+}
+
+/// Compares the walltime results against the baseline if any, and saves them either as a new
+/// baseline or in the profile folder
+fn save_walltime_results(
+    metadata: &Metadata,
+    config: &RunConfig,
+    baseline: Option<(&str, &WalltimeResults)>,
+    results: &WalltimeResults,
+) -> Result<()> {
+    if let Some((name, baseline)) = baseline {
+        eprintln!("Comparing against baseline `{name}`\n");
+        // Keep stdout for JSON messages only
+        let mut out: Box<dyn Write> = match config.message_format {
+            MessageFormat::Human => Box::new(io::stdout()),
+            MessageFormat::Json => Box::new(io::stderr()),
+        };
+        print_comparison(&mut out, baseline, results, CompareConfig::default())?;
+    }
+
+    let results_path = match &config.save_baseline {
+        Some(name) => {
+            let baseline_path = get_baseline_path(metadata, name)?;
+            std::fs::create_dir_all(baseline_path.parent().unwrap())
+                .context("Failed to create baselines folder")?;
+            eprintln!("Saving baseline `{name}` to {}", baseline_path.display());
+            baseline_path
+        }
+        None => {
+            let results_folder = std::env::var("CODSPEED_PROFILE_FOLDER")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| metadata.target_directory.join("codspeed/profiles").into())
+                .join("results");
+            std::fs::create_dir_all(&results_folder).context("Failed to create results folder")?;
+            results_folder.join(format!("{}.json", std::process::id()))
+        }
+    };
+    write_walltime_results(&results_path, results)?;
     Ok(())
 }
 
-fn aggregate_raw_walltime_data(workspace_root: &Path) -> Result<()> {
-    let results = WalltimeResults::collect_walltime_results(workspace_root)
+/// Collects the raw walltime results written by the benchmark processes, merging the runs of
+/// each benchmark with `--repeat`
+///
+/// Returns `None` if no walltime benchmark was found.
+fn aggregate_raw_walltime_data(
+    target_dir: &Path,
+    config: &RunConfig,
+    benchmark_counts: &mut BenchmarkCounts,
+) -> Result<Option<WalltimeResults>> {
+    let mut results = WalltimeResults::collect_walltime_results(target_dir)
         .with_context(|| {
             format!(
                 "Failed to collect walltime results. This may be due to version incompatibility. \
@@ -184,7 +898,10 @@ fn aggregate_raw_walltime_data(workspace_root: &Path) -> Result<()> {
 
     if results.benchmarks().is_empty() {
         eprintln!("No walltime benchmarks found");
-        return Ok(());
+        return Ok(None);
+    }
+    if config.repeat > 1 {
+        results = results.merge_runs(config.keep_raw_samples)?;
     }
 
     for bench in results.benchmarks() {
@@ -194,18 +911,75 @@ fn aggregate_raw_walltime_data(workspace_root: &Path) -> Result<()> {
                 bench.name()
             );
         }
+        if config.message_format == MessageFormat::Json {
+            Message::BenchmarkMeasured {
+                uri: bench.uri(),
+                name: bench.name(),
+            }
+            .emit();
+        }
+        benchmark_counts.measured += 1;
     }
 
-    let results_folder = std::env::var("CODSPEED_PROFILE_FOLDER")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| workspace_root.join("target/codspeed/profiles"))
-        .join("results");
-    std::fs::create_dir_all(&results_folder).context("Failed to create results folder")?;
+    Ok(Some(results))
+}
+
+/// Runs the benchmark process, forwarding the JSON messages it prints on stdout with the suite
+/// they belong to. Other lines are forwarded to stderr, so that stdout only contains JSON.
+fn run_with_json_messages(
+    command: &mut Command,
+    bench: &BenchToRun,
+    benchmark_counts: &mut BenchmarkCounts,
+    timeout: Option<Duration>,
+) -> io::Result<SuiteOutcome> {
+    let mut suite = spawn_suite(command.stdout(Stdio::piped()), timeout)?;
+    let stdout = suite
+        .child
+        .stdout
+        .take()
+        .expect("Unable to get stdout for child process");
+
+    // Messages are forwarded while waiting, so that a hanging process can still be killed
+    std::thread::scope(|scope| {
+        let forwarder =
+            scope.spawn(|| forward_json_messages(BufReader::new(stdout), bench, benchmark_counts));
+        let outcome = wait_for_suite(&mut suite.child, timeout)?;
+        forwarder.join().expect("Failed to forward messages")?;
+        io::Result::Ok(outcome)
+    })
+}
+
+/// Forwards the JSON messages printed by a benchmark process with the suite they belong to, and
+/// the other lines to stderr
+fn forward_json_messages(
+    output: impl BufRead,
+    bench: &BenchToRun,
+    benchmark_counts: &mut BenchmarkCounts,
+) -> io::Result<()> {
+    for line in output.lines() {
+        let line = line?;
+        match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&line) {
+            Result::Ok(mut message) if message.contains_key("reason") => {
+                if let Some(reason) = message["reason"].as_str() {
+                    benchmark_counts.record(reason);
+                }
+                message.insert("package".into(), bench.package_name.clone().into());
+                message.insert(
+                    "bench_target".into(),
+                    bench.bench_target_name.clone().into(),
+                );
+                println!("{}", serde_json::Value::Object(message));
+            }
+            _ => eprintln!("{line}"),
+        }
+    }
+
+    io::Result::Ok(())
+}
 
-    let results_path = results_folder.join(format!("{}.json", std::process::id()));
-    let mut results_file =
-        std::fs::File::create(&results_path).context("Failed to create results file")?;
-    serde_json::to_writer_pretty(&results_file, &results)?;
+fn write_walltime_results(path: &Path, results: &WalltimeResults) -> Result<()> {
+    let mut results_file = std::fs::File::create(path).context("Failed to create results file")?;
+    serde_json::to_writer_pretty(&results_file, results)?;
     results_file
         .flush()
         .context("Failed to flush results file")?;
@@ -0,0 +1,79 @@
+use crate::{helpers::split_command, prelude::*};
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// Finds the runner used by cargo to execute the binaries built for a target, from the
+/// `CARGO_TARGET_<TRIPLE>_RUNNER` environment variable or the `target.<triple>.runner` setting
+/// of the cargo configuration files
+///
+/// `target.<cfg>.runner` settings are not supported. Cargo splits a runner given as a string on
+/// whitespace, while it is split like `--wrapper` here, following the quoting rules of a shell, so
+/// that a path containing spaces can be quoted. Both only differ for runners containing quotes or
+/// backslashes.
+pub fn get_target_runner(target: &str) -> Result<Option<Vec<String>>> {
+    let env_var = format!(
+        "CARGO_TARGET_{}_RUNNER",
+        target.to_uppercase().replace(['-', '.'], "_")
+    );
+    if let Result::Ok(runner) = std::env::var(&env_var) {
+        return split_command("runner", &runner).map(Some);
+    }
+
+    for config_path in cargo_config_paths()? {
+        let content = std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Failed to read cargo config at {}", config_path.display()))?;
+        let config: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse TOML in {}", config_path.display()))?;
+        let Some(runner) = config
+            .get("target")
+            .and_then(|targets| targets.get(target))
+            .and_then(|target| target.get("runner"))
+        else {
+            continue;
+        };
+        let runner = match runner {
+            toml::Value::String(runner) => split_command("runner", runner)?,
+            toml::Value::Array(args) => args
+                .iter()
+                .map(|arg| arg.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Invalid `target.{target}.runner` in {}",
+                        config_path.display()
+                    )
+                })?,
+            _ => bail!(
+                "Invalid `target.{target}.runner` in {}",
+                config_path.display()
+            ),
+        };
+        return Ok(Some(runner));
+    }
+
+    Ok(None)
+}
+
+/// Cargo configuration files, by order of precedence
+///
+/// See [Hierarchical structure](https://doc.rust-lang.org/cargo/reference/config.html#hierarchical-structure)
+fn cargo_config_paths() -> Result<Vec<PathBuf>> {
+    let current_dir = std::env::current_dir()?;
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")));
+
+    let config_dirs = current_dir
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .chain(cargo_home)
+        .unique();
+    Ok(config_dirs
+        .filter_map(|dir| {
+            ["config.toml", "config"]
+                .into_iter()
+                .map(|file_name| dir.join(file_name))
+                .find(|path| path.is_file())
+        })
+        .collect())
+}
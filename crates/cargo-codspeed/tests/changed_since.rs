@@ -0,0 +1,173 @@
+use predicates::str::contains;
+use std::{fs, path::Path, process::Command};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/workspace.in";
+
+fn git(dir: &String, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+/// Builds the workspace and commits it, including the generated lockfile
+fn init_repository(dir: &String) {
+    cargo_codspeed(dir).arg("build").assert().success();
+    fs::write(Path::new(dir).join(".gitignore"), "target\n").unwrap();
+    git(dir, &["init", "--quiet"]);
+    git(dir, &["add", "--all"]);
+    git(dir, &["commit", "--quiet", "--message", "initial"]);
+}
+
+fn setup_repository() -> String {
+    let dir = setup(DIR, Project::Workspace);
+    init_repository(&dir);
+    dir
+}
+
+/// Runs the suites affected by the changes since `HEAD`, returning the suites it ran
+fn run_changed(dir: &String) -> Vec<String> {
+    let output = cargo_codspeed(dir)
+        .args(["run", "--changed-since", "HEAD"])
+        .assert()
+        .success()
+        .stderr(contains("affected by the changes since `HEAD`"));
+    let mut suites = ran_suites(&output.get_output().stderr);
+    suites.sort();
+    suites
+}
+
+#[test]
+fn test_run_changed_since() {
+    let dir = setup_repository();
+    assert!(run_changed(&dir).is_empty());
+
+    fs::write(Path::new(&dir).join("NOTES.md"), "notes").unwrap();
+    cargo_codspeed(&dir)
+        .args(["run", "--changed-since", "HEAD"])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Warning: None of the 1 file(s) changed since `HEAD` belong to a package of the workspace",
+        ));
+
+    let bench_path = Path::new(&dir).join("b/benches/bencher_example.rs");
+    let mut source = fs::read_to_string(&bench_path).unwrap();
+    source.push_str("\n// changed\n");
+    fs::write(&bench_path, source).unwrap();
+    let suites = run_changed(&dir);
+    assert!(!suites.is_empty());
+    assert!(suites.iter().all(|suite| suite.starts_with("package-b ")));
+
+    // Untracked files are changes too
+    fs::write(Path::new(&dir).join("a/notes.txt"), "notes").unwrap();
+    let suites = run_changed(&dir);
+    assert!(suites.iter().any(|suite| suite.starts_with("package-a ")));
+    assert!(suites.iter().any(|suite| suite.starts_with("package-b ")));
+    teardown(dir);
+}
+
+#[test]
+fn test_changed_since_workspace_manifest() {
+    let dir = setup_repository();
+    let manifest_path = Path::new(&dir).join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).unwrap();
+    manifest.push_str("\n# changed\n");
+    fs::write(&manifest_path, manifest).unwrap();
+    let suites = run_changed(&dir);
+    assert!(suites.iter().any(|suite| suite.starts_with("package-a ")));
+    assert!(suites.iter().any(|suite| suite.starts_with("package-b ")));
+    teardown(dir);
+}
+
+#[test]
+fn test_changed_since_nothing_selected_json() {
+    let dir = setup_repository();
+    let output = cargo_codspeed(&dir)
+        .args(["run", "--changed-since", "HEAD", "--message-format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let summary = serde_json::from_str::<serde_json::Value>(
+        output.lines().next_back().expect("No JSON message"),
+    )
+    .unwrap();
+    assert_eq!(summary["reason"], "run-finished");
+    assert_eq!(summary["suites"], 0);
+    teardown(dir);
+}
+
+#[test]
+fn test_changed_since_quoted_paths() {
+    let dir = setup_repository();
+    // git quotes non-ASCII paths unless asked for NUL separated output
+    fs::write(Path::new(&dir).join("b/café.txt"), "notes").unwrap();
+    git(&dir, &["add", "--all"]);
+    git(&dir, &["commit", "--quiet", "--message", "notes"]);
+    fs::write(Path::new(&dir).join("b/café.txt"), "changed").unwrap();
+    let suites = run_changed(&dir);
+    assert!(!suites.is_empty());
+    assert!(suites.iter().all(|suite| suite.starts_with("package-b ")));
+
+    fs::write(Path::new(&dir).join("a/ünïcödé.txt"), "notes").unwrap();
+    let suites = run_changed(&dir);
+    assert!(suites.iter().any(|suite| suite.starts_with("package-a ")));
+    teardown(dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_changed_since_symlinked_package() {
+    let dir = setup(DIR, Project::Workspace);
+    // Cargo sees the package through the symlink, while git lists the files of its target
+    std::os::unix::fs::symlink("b", Path::new(&dir).join("linked-b")).unwrap();
+    fs::write(
+        Path::new(&dir).join("Cargo.toml"),
+        "[workspace]\nmembers = [\"a\", \"linked-b\"]\n",
+    )
+    .unwrap();
+    init_repository(&dir);
+
+    let bench_path = Path::new(&dir).join("b/benches/bencher_example.rs");
+    let mut source = fs::read_to_string(&bench_path).unwrap();
+    source.push_str("\n// changed\n");
+    fs::write(&bench_path, source).unwrap();
+    let suites = run_changed(&dir);
+    assert!(!suites.is_empty());
+    assert!(suites.iter().all(|suite| suite.starts_with("package-b ")));
+    teardown(dir);
+}
+
+#[test]
+fn test_changed_since_invalid_ref() {
+    let dir = setup_repository();
+    cargo_codspeed(&dir)
+        .args(["run", "--changed-since", "does-not-exist"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Failed to list the files changed since `does-not-exist`",
+        ));
+    // A reference starting with a dash is not read as an option of git
+    let output_path = Path::new(&dir).join("diff.txt");
+    let git_ref = format!("--output={}", output_path.display());
+    cargo_codspeed(&dir)
+        .arg("run")
+        .arg(format!("--changed-since={git_ref}"))
+        .assert()
+        .failure()
+        .stderr(contains(format!(
+            "Failed to list the files changed since `{git_ref}`"
+        )));
+    assert!(!output_path.exists());
+    teardown(dir);
+}
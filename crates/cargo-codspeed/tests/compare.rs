@@ -0,0 +1,48 @@
+use assert_cmd::Command;
+use predicates::str::{contains, is_match};
+
+const BASELINE: &str = "tests/compare.in/baseline.json";
+const CURRENT: &str = "tests/compare.in/current.json";
+
+fn cargo_codspeed_compare() -> Command {
+    let mut cmd = Command::cargo_bin("cargo-codspeed").unwrap();
+    cmd.arg("compare");
+    cmd
+}
+
+#[test]
+fn test_compare_verdicts() {
+    cargo_codspeed_compare()
+        .args([BASELINE, CURRENT])
+        .assert()
+        .success()
+        .stdout(is_match(r"fib_20 .* \+20\.00% .* \+25\.00% .* regressed").unwrap())
+        .stdout(is_match(r"fib_10 .* -22\.22% .* improved").unwrap())
+        .stdout(is_match(r"bubble_sort .* \+0\.20% .* no change").unwrap())
+        .stdout(is_match(r"new_bench .* new").unwrap())
+        .stdout(is_match(r"removed_bench .* removed").unwrap())
+        .stderr(contains(
+            "Compared 5 benchmark(s): 1 improved, 1 regressed, 1 unchanged",
+        ));
+}
+
+#[test]
+fn test_compare_noise_threshold() {
+    cargo_codspeed_compare()
+        .args([BASELINE, CURRENT])
+        .args(["--noise-threshold", "0.5"])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Compared 5 benchmark(s): 0 improved, 0 regressed, 3 unchanged",
+        ));
+}
+
+#[test]
+fn test_compare_missing_file() {
+    cargo_codspeed_compare()
+        .args([BASELINE, "tests/compare.in/missing.json"])
+        .assert()
+        .failure()
+        .stderr(contains("Failed to open walltime results"));
+}
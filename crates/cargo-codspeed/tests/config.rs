@@ -0,0 +1,187 @@
+use predicates::str::contains;
+use std::{fs, path::Path};
+
+mod helpers;
+use helpers::*;
+
+#[test]
+fn test_package_config() {
+    let dir = setup("tests/simple-criterion.in", Project::Simple);
+    append_to_manifest(
+        &dir,
+        r#"[package.metadata.codspeed]
+measurement-mode = ["walltime"]
+exclude-benches = ["another_criterion_example"]
+"#,
+    );
+    cargo_codspeed(&dir)
+        .arg("build")
+        .assert()
+        .success()
+        .stderr(contains("Measurement mode: walltime"))
+        .stderr(contains(
+            "Skipping excluded benchmark `another_criterion_example` in package `simple-criterion`",
+        ))
+        .stderr(contains("Built 1 benchmark suite(s)"));
+    cargo_codspeed(&dir)
+        .arg("run")
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .stderr(contains("Measurement mode: Walltime"))
+        .stderr(contains("Finished running 1 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_excluded_bench_selected_explicitly() {
+    let dir = setup("tests/simple-criterion.in", Project::Simple);
+    append_to_manifest(
+        &dir,
+        r#"[package.metadata.codspeed]
+exclude-benches = ["another_criterion_example"]
+"#,
+    );
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "another_criterion_example"])
+        .assert()
+        .success()
+        .stderr(contains("Built 1 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_workspace_config() {
+    let dir = setup("tests/workspace.in", Project::Workspace);
+    append_to_manifest(
+        &dir,
+        r#"[workspace.metadata.codspeed]
+exclude-benches = ["another_bencher_example"]
+"#,
+    );
+    cargo_codspeed(&dir)
+        .arg("build")
+        .assert()
+        .success()
+        .stderr(contains("Built 2 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_invalid_config() {
+    let dir = setup("tests/simple-criterion.in", Project::Simple);
+    append_to_manifest(
+        &dir,
+        r#"[package.metadata.codspeed]
+exclude-benches = "criterion_example"
+"#,
+    );
+    cargo_codspeed(&dir)
+        .arg("build")
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Invalid CodSpeed configuration in `[package.metadata.codspeed]` of package simple-criterion",
+        ))
+        .stderr(contains("invalid type: string \"criterion_example\", expected a sequence"));
+    teardown(dir);
+}
+
+#[test]
+fn test_unknown_config_keys() {
+    let dir = setup("tests/simple-criterion.in", Project::Simple);
+    append_to_manifest(
+        &dir,
+        r#"[package.metadata.codspeed]
+unknown-setting = true
+exclude-benches = ["another_criterion_example"]
+
+[package.metadata.codspeed.benches.criterion_example]
+unknown-bench-setting = 1
+"#,
+    );
+    cargo_codspeed(&dir)
+        .arg("build")
+        .assert()
+        .success()
+        .stderr(contains(
+            "Warning: Unknown key `unknown-setting` in `[package.metadata.codspeed]` of package simple-criterion, it is ignored",
+        ))
+        .stderr(contains(
+            "Warning: Unknown key `benches.criterion_example.unknown-bench-setting` in `[package.metadata.codspeed]` of package simple-criterion, it is ignored",
+        ))
+        .stderr(contains("Built 1 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_workspace_settings_in_member() {
+    let dir = setup("tests/workspace.in", Project::Workspace);
+    append_to_manifest(
+        &format!("{dir}/a"),
+        r#"[package.metadata.codspeed]
+profile = "release"
+"#,
+    );
+    cargo_codspeed(&dir)
+        .arg("build")
+        .assert()
+        .success()
+        .stderr(contains(
+            "Warning: `profile` is ignored in `[package.metadata.codspeed]` of package package-a, it is only read from `[workspace.metadata.codspeed]` or from the root package of the workspace",
+        ))
+        .stderr(contains("Built 3 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_bench_target_env_and_args() {
+    let dir = setup("tests/simple-criterion.in", Project::Simple);
+    append_to_manifest(
+        &dir,
+        r#"[workspace.metadata.codspeed]
+env = { FIXTURE = "workspace", WORKSPACE = "workspace" }
+
+[package.metadata.codspeed]
+env = { FIXTURE = "package", OTHER = "package" }
+bench-args = ["--package-arg"]
+
+[package.metadata.codspeed.benches.criterion_example]
+env = { FIXTURE = "target" }
+bench-args = ["--target-arg"]
+"#,
+    );
+    // Prints what each benchmark binary would receive instead of running it
+    let wrapper_path = Path::new(&dir).join("wrapper.sh");
+    fs::write(
+        &wrapper_path,
+        "echo \"$(basename \"$1\") FIXTURE=$FIXTURE OTHER=$OTHER WORKSPACE=$WORKSPACE ARGS=$2\"\n",
+    )
+    .unwrap();
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["run", "--env", "OTHER=cli", "--wrapper"])
+        .arg(format!("sh {}", wrapper_path.display()))
+        .assert()
+        .success()
+        .stdout(contains(
+            "criterion_example FIXTURE=target OTHER=cli WORKSPACE=workspace ARGS=--target-arg",
+        ))
+        .stdout(contains(
+            "another_criterion_example FIXTURE=package OTHER=cli WORKSPACE=workspace ARGS=--package-arg",
+        ));
+    teardown(dir);
+}
+
+#[test]
+fn test_invalid_env_var() {
+    let dir = setup("tests/simple-criterion.in", Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["run", "--env", "FIXTURE"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Invalid environment variable `FIXTURE`, expected `KEY=VALUE`",
+        ));
+    teardown(dir);
+}
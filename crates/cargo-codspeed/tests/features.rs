@@ -74,3 +74,30 @@ fn test_all_features() {
         .stdout(contains("with_default_feature"));
     teardown(dir);
 }
+
+#[test]
+fn test_required_features() {
+    let dir = setup(DIR, Project::Features);
+    // Appended to the last `[[bench]]` section of the manifest
+    append_to_manifest(&dir, "required-features = [\"sample_feature\"]\n");
+
+    cargo_codspeed(&dir)
+        .arg("build")
+        .assert()
+        .failure()
+        .stderr(contains(
+            "The following benchmark targets were skipped because of their `required-features`",
+        ))
+        .stderr(contains(
+            "`bench` in package `features`, requires `sample_feature`",
+        ));
+    cargo_codspeed(&dir)
+        .arg("build")
+        .arg("-F")
+        .arg("sample_feature")
+        .assert()
+        .success()
+        .stderr(contains("required-features").not())
+        .stderr(contains("Built 1 benchmark suite(s)"));
+    teardown(dir);
+}
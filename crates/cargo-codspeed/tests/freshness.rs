@@ -0,0 +1,108 @@
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::{fs, path::Path};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_run_detects_stale_benchmarks() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "--require-fresh"])
+        .assert()
+        .success()
+        .stderr(contains("changed since").not());
+
+    let bench_path = Path::new(&dir).join("benches/criterion_example.rs");
+    let source = fs::read_to_string(&bench_path).unwrap();
+    fs::write(
+        &bench_path,
+        format!("{source}\n// Changed after the build\n"),
+    )
+    .unwrap();
+
+    cargo_codspeed(&dir)
+        .arg("run")
+        .assert()
+        .success()
+        .stderr(contains(
+            "Warning: The following benchmarks were built from sources that changed since:",
+        ))
+        .stderr(contains(
+            "`criterion_example` in package `simple-criterion`",
+        ));
+    cargo_codspeed(&dir)
+        .args(["run", "--require-fresh"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "The following benchmarks were built from sources that changed since:",
+        ))
+        .stderr(contains("Running").not());
+
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "--require-fresh"])
+        .assert()
+        .success();
+    teardown(dir);
+}
+
+#[test]
+fn test_freshness_nested_target_and_symlinks() {
+    let dir = setup(DIR, Project::Simple);
+    let data_dir = Path::new(&dir).join("benches/target");
+    fs::create_dir_all(&data_dir).unwrap();
+    fs::write(data_dir.join("data.txt"), "data").unwrap();
+    // A symlink cycle must not be followed
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("..", Path::new(&dir).join("benches/parent")).unwrap();
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "--require-fresh"])
+        .assert()
+        .success();
+
+    // Only the target directory of the workspace is ignored
+    fs::write(data_dir.join("data.txt"), "changed data").unwrap();
+    cargo_codspeed(&dir)
+        .args(["run", "--require-fresh"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "The following benchmarks were built from sources that changed since:",
+        ));
+    teardown(dir);
+}
+
+#[test]
+fn test_freshness_ignores_files_outside_sources() {
+    let dir = setup(DIR, Project::Simple);
+    let notes_path = Path::new(&dir).join("notes.txt");
+    fs::write(&notes_path, "notes").unwrap();
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+
+    // Only the manifests and the directories of the targets are inputs of the build
+    fs::write(&notes_path, "changed notes").unwrap();
+    cargo_codspeed(&dir)
+        .args(["run", "--require-fresh"])
+        .assert()
+        .success()
+        .stderr(contains("changed since").not());
+    teardown(dir);
+}
@@ -1,6 +1,6 @@
 use std::env;
 use std::env::temp_dir;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use assert_cmd::Command;
@@ -91,3 +91,37 @@ pub fn cargo_codspeed(dir: &String) -> Command {
     cmd.current_dir(dir);
     cmd
 }
+
+/// Appends some TOML to the manifest in `dir`
+#[allow(dead_code)]
+pub fn append_to_manifest(dir: &str, toml: &str) {
+    let manifest_path = Path::new(dir).join("Cargo.toml");
+    let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+    std::fs::write(manifest_path, format!("{manifest}\n{toml}")).unwrap();
+}
+
+/// Suites run by `cargo codspeed run`, as `<package> <bench target>`, from its stderr
+#[allow(dead_code)]
+pub fn ran_suites(stderr: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Running "))
+        .map(String::from)
+        .collect()
+}
+
+/// Runs a walltime benchmark built in `dir` directly, as `cargo bench` would, outside of
+/// `cargo codspeed`
+#[allow(dead_code)]
+pub fn walltime_bench(dir: &str, package: &str, bench: &str) -> Command {
+    let path = Path::new(dir)
+        .join("target/codspeed/walltime/bench")
+        .join(package)
+        .join(format!("{bench}{}", env::consts::EXE_SUFFIX));
+    let mut cmd = Command::new(path);
+    cmd.current_dir(dir)
+        .arg("--bench")
+        .env_remove("CODSPEED_ENV")
+        .env_remove("CODSPEED_CARGO_WORKSPACE_ROOT");
+    cmd
+}
@@ -0,0 +1,159 @@
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_list_without_build() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(contains("No benchmarks found"));
+    teardown(dir);
+}
+
+#[test]
+fn test_list_all() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(contains("fib 20"))
+        .stdout(contains("bubble sort"))
+        .stdout(contains("Harness").not())
+        .stderr(contains("Found 2 benchmark(s) in 2 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_list_filters() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["list", "--bench", "criterion_example"])
+        .assert()
+        .success()
+        .stdout(contains("fib 20"))
+        .stdout(contains("bubble sort").not());
+    cargo_codspeed(&dir)
+        .args(["list", "bubble"])
+        .assert()
+        .success()
+        .stdout(contains("bubble sort"))
+        .stdout(contains("fib 20").not());
+    teardown(dir);
+}
+
+#[test]
+fn test_list_skip_and_exact() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["list", "--skip", "bubble"])
+        .assert()
+        .success()
+        .stdout(contains("fib 20"))
+        .stdout(contains("bubble sort").not());
+    cargo_codspeed(&dir)
+        .args(["list", "--skip", "bubble", "--skip", "fib"])
+        .assert()
+        .success()
+        .stderr(contains("Found 0 benchmark(s) in 2 benchmark suite(s)"));
+
+    let output = cargo_codspeed(&dir)
+        .args(["list", "--bench", "criterion_example"])
+        .output()
+        .unwrap();
+    let uri = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    cargo_codspeed(&dir)
+        .args(["list", "--exact", "fib"])
+        .assert()
+        .success()
+        .stderr(contains("Found 0 benchmark(s)"));
+    cargo_codspeed(&dir)
+        .args(["list", "--exact", &uri])
+        .assert()
+        .success()
+        .stdout(contains("fib 20"))
+        .stderr(contains("Found 1 benchmark(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_walltime_run_exact() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+
+    // The walltime harnesses match the filters on the same URIs as the instrumented ones
+    let output = cargo_codspeed(&dir)
+        .args(["list", "-m", "walltime", "--bench", "criterion_example"])
+        .output()
+        .unwrap();
+    let uri = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--exact", &uri])
+        .assert()
+        .success()
+        .stdout(contains("fib 20"))
+        .stdout(contains("bubble sort").not());
+    // The criterion ids still match as well
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--exact", "fib 20"])
+        .assert()
+        .success()
+        .stdout(contains("fib 20"))
+        .stdout(contains("bubble sort").not());
+    teardown(dir);
+}
+
+#[test]
+fn test_list_walltime() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(contains(
+            "No benchmarks found for the simulation mode. Run `cargo codspeed build -m simulation` first.",
+        ));
+    cargo_codspeed(&dir)
+        .args(["list", "-m", "walltime"])
+        .assert()
+        .success()
+        .stdout(contains("fib 20"))
+        .stdout(contains("bubble sort"))
+        .stderr(contains("Found 2 benchmark(s) in 2 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_list_same_uris_in_all_modes() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "simulation,walltime"])
+        .assert()
+        .success();
+    let list = |mode: &str| {
+        let output = cargo_codspeed(&dir)
+            .args(["list", "-m", mode])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(list("simulation"), list("walltime"));
+    teardown(dir);
+}
@@ -0,0 +1,118 @@
+use predicates::str::contains;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+fn manifest_path(dir: &str) -> std::path::PathBuf {
+    Path::new(dir).join("target/codspeed/analysis/bench/manifest.json")
+}
+
+#[test]
+fn test_build_writes_manifest() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(manifest_path(&dir)).unwrap()).unwrap();
+    assert_eq!(
+        manifest["cargo_codspeed_version"],
+        env!("CARGO_PKG_VERSION")
+    );
+    assert_eq!(manifest["profile"], "bench");
+    assert_eq!(
+        manifest["compat_versions"]["codspeed-criterion-compat"],
+        env!("CARGO_PKG_VERSION")
+    );
+    let benches = manifest["benches"].as_array().unwrap();
+    assert_eq!(benches.len(), 2);
+    let bench = benches
+        .iter()
+        .find(|bench| bench["bench"] == "criterion_example")
+        .unwrap();
+    assert_eq!(bench["package"], "simple-criterion");
+    assert_eq!(
+        bench["executable_path"],
+        Path::new("simple-criterion")
+            .join("criterion_example")
+            .to_str()
+            .unwrap()
+    );
+    assert!(bench["rustflags"]
+        .as_array()
+        .unwrap()
+        .contains(&Value::from("--cfg=codspeed")));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_warns_about_other_major_version() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+
+    let mut manifest: Value =
+        serde_json::from_str(&fs::read_to_string(manifest_path(&dir)).unwrap()).unwrap();
+    manifest["cargo_codspeed_version"] = "1.0.0".into();
+    manifest["compat_versions"]["codspeed-criterion-compat"] = "1.0.0".into();
+    fs::write(manifest_path(&dir), manifest.to_string()).unwrap();
+
+    cargo_codspeed(&dir)
+        .arg("run")
+        .assert()
+        .success()
+        .stderr(contains(
+            "Warning: The benchmarks were built by cargo-codspeed v1.0.0",
+        ))
+        .stderr(contains(
+            "Warning: The benchmarks depend on codspeed-criterion-compat v1.0.0",
+        ))
+        .stderr(contains("Finished running 1 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_ignores_unlisted_executables() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    fs::write(
+        Path::new(&dir).join("target/codspeed/analysis/bench/simple-criterion/not_a_bench"),
+        "",
+    )
+    .unwrap();
+
+    cargo_codspeed(&dir)
+        .arg("run")
+        .assert()
+        .success()
+        .stderr(contains("Finished running 1 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_asks_to_rebuild_builds_without_manifest() {
+    let dir = setup(DIR, Project::Simple);
+    // Layout of the builds of the versions without a build manifest
+    let legacy_dir = Path::new(&dir).join("target/codspeed/analysis/simple-criterion");
+    fs::create_dir_all(&legacy_dir).unwrap();
+    fs::write(legacy_dir.join("criterion_example"), "").unwrap();
+
+    for command in ["run", "list"] {
+        cargo_codspeed(&dir)
+            .arg(command)
+            .assert()
+            .failure()
+            .stderr(contains(
+                "were built by an older version of cargo-codspeed, which stored them without a build manifest. Run `cargo codspeed build -m simulation` to rebuild them.",
+            ));
+    }
+    teardown(dir);
+}
@@ -1,4 +1,5 @@
-use predicates::str::contains;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::path::Path;
 
 mod helpers;
 use helpers::*;
@@ -30,3 +31,91 @@ fn test_build_multiple_measurement_modes_comma_separated() {
         ));
     teardown(dir);
 }
+
+#[test]
+fn test_run_multiple_measurement_modes() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args([
+            "build",
+            "-m",
+            "simulation,walltime",
+            "--bench",
+            "criterion_example",
+        ])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "simulation,walltime"])
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .stderr(contains("[cargo-codspeed] Measurement mode: Simulation"))
+        .stderr(contains("[cargo-codspeed] Measurement mode: Walltime"));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_configured_measurement_modes() {
+    let dir = setup(DIR, Project::Simple);
+    append_to_manifest(
+        &dir,
+        "[package.metadata.codspeed]\nmeasurement-mode = [\"simulation\", \"walltime\"]\n",
+    );
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .arg("run")
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .stderr(contains("[cargo-codspeed] Measurement mode: Simulation"))
+        .stderr(contains("[cargo-codspeed] Measurement mode: Walltime"));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_multiple_measurement_modes_walltime_options() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args([
+            "build",
+            "-m",
+            "simulation,walltime",
+            "--bench",
+            "criterion_example",
+        ])
+        .assert()
+        .success();
+    // The walltime-only options apply to the walltime mode and are ignored for the others
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "simulation,walltime"])
+        .args(["--save-baseline", "main", "--repeat", "2"])
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .stderr(contains("[cargo-codspeed] Measurement mode: Simulation"))
+        .stderr(contains("Saving baseline `main`"))
+        .stderr(contains("Running each benchmark suite 2 times").count(1));
+    assert!(Path::new(&dir)
+        .join("target/codspeed/baselines/main.json")
+        .exists());
+    teardown(dir);
+}
+
+#[test]
+fn test_run_multiple_measurement_modes_checks_options_first() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "simulation", "-m", "memory"])
+        .args(["--save-baseline", "main"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "--save-baseline and --baseline are only supported in walltime mode",
+        ))
+        .stderr(contains("Measurement mode:").not());
+    teardown(dir);
+}
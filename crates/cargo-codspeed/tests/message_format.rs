@@ -0,0 +1,131 @@
+use serde_json::Value;
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_run_message_format_json() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    let output = cargo_codspeed(&dir)
+        .args(["run", "--message-format", "json"])
+        .args(["--bench", "criterion_example"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // stdout must only contain JSON messages
+    let messages = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    let reasons = messages
+        .iter()
+        .map(|m| m["reason"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(reasons.first(), Some(&"suite-started"));
+    assert_eq!(reasons.last(), Some(&"run-finished"));
+    assert!(reasons.contains(&"suite-finished"));
+
+    let benchmark = messages
+        .iter()
+        .find(|m| m["reason"] == "benchmark-checked")
+        .expect("No benchmark message");
+    assert_eq!(benchmark["package"], "simple-criterion");
+    assert_eq!(benchmark["bench_target"], "criterion_example");
+    assert!(benchmark["uri"].as_str().unwrap().ends_with("fib 20"));
+
+    let summary = messages.last().unwrap();
+    assert_eq!(summary["measurement_mode"], "simulation");
+    assert_eq!(summary["suites"], 1);
+    assert_eq!(summary["benchmarks"]["checked"], 1);
+    teardown(dir);
+}
+
+#[test]
+fn test_run_message_format_json_walltime() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+    let output = cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--message-format", "json"])
+        .args(["--bench", "criterion_example"])
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let messages = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .collect::<Vec<_>>();
+
+    // The walltime harnesses do not emit messages, the benchmarks are reported from their results
+    let benchmark = messages
+        .iter()
+        .find(|m| m["reason"] == "benchmark-measured")
+        .expect("No benchmark message");
+    assert!(benchmark["uri"].as_str().unwrap().ends_with("fib 20"));
+
+    let summary = messages.last().unwrap();
+    assert_eq!(summary["reason"], "run-finished");
+    assert_eq!(summary["measurement_mode"], "walltime");
+    assert_eq!(summary["benchmarks"]["measured"], 1);
+    teardown(dir);
+}
+
+#[test]
+fn test_run_message_format_json_parallel() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    let output = cargo_codspeed(&dir)
+        .args(["run", "--message-format", "json", "--jobs", "2"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let reasons = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["reason"].clone())
+        .collect::<Vec<_>>();
+    // Both suites are reported as started when they are spawned, before either finishes
+    assert_eq!(reasons[..2], ["suite-started", "suite-started"]);
+    assert_eq!(reasons.iter().filter(|r| *r == "suite-finished").count(), 2);
+    teardown(dir);
+}
+
+#[test]
+fn test_run_message_format_json_failure() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    // Unknown arguments make the benchmark processes fail
+    let output = cargo_codspeed(&dir)
+        .args(["run", "--message-format", "json", "--", "--unknown-flag"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+
+    // The summary is emitted even though the run stops at the first failure
+    let stdout = String::from_utf8(output).unwrap();
+    let summary = serde_json::from_str::<Value>(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(summary["reason"], "run-finished");
+    assert_eq!(summary["suites"], 2);
+    assert_eq!(summary["failed_suites"], 1);
+    teardown(dir);
+}
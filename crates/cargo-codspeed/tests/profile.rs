@@ -0,0 +1,52 @@
+use predicates::str::contains;
+use std::path::Path;
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_build_multiple_profiles() {
+    let dir = setup(DIR, Project::Simple);
+    append_to_manifest(
+        &dir,
+        "[profile.release-lto]\ninherits = \"bench\"\nlto = true\n",
+    );
+
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["build", "--profile", "release-lto"])
+        .assert()
+        .success();
+    let codspeed_target_dir = Path::new(&dir).join("target/codspeed/analysis");
+    assert!(codspeed_target_dir
+        .join("bench/simple-criterion/criterion_example")
+        .exists());
+    assert!(codspeed_target_dir
+        .join("release-lto/simple-criterion/criterion_example")
+        .exists());
+
+    // The default profile only contains the first build
+    cargo_codspeed(&dir)
+        .arg("run")
+        .assert()
+        .success()
+        .stderr(contains("Finished running 1 benchmark suite(s)"));
+    cargo_codspeed(&dir)
+        .args(["run", "--profile", "release-lto"])
+        .assert()
+        .success()
+        .stderr(contains("Finished running 2 benchmark suite(s)"));
+    cargo_codspeed(&dir)
+        .args(["run", "--profile", "unknown"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Run `cargo codspeed build -m simulation --profile unknown` first.",
+        ));
+    teardown(dir);
+}
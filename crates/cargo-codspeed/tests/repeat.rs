@@ -0,0 +1,74 @@
+use predicates::str::contains;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_run_repeat_merges_walltime_results() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--repeat", "2", "--interleave"])
+        .args(["--save-baseline", "repeated"])
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .stderr(contains("Running each benchmark suite 2 times"));
+
+    let results: Value = serde_json::from_str(
+        &fs::read_to_string(Path::new(&dir).join("target/codspeed/baselines/repeated.json"))
+            .unwrap(),
+    )
+    .unwrap();
+    let benchmarks = results["benchmarks"].as_array().unwrap();
+    assert_eq!(benchmarks.len(), 1);
+    assert!(benchmarks[0]["uri"].as_str().unwrap().ends_with("fib 20"));
+    // The raw samples are only needed to merge the runs
+    assert!(benchmarks[0].get("raw_samples").is_none());
+    teardown(dir);
+}
+
+#[test]
+fn test_repeat_keep_raw_samples_from_env() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    let raw_samples = |keep_raw_samples: &str| {
+        cargo_codspeed(&dir)
+            .args(["run", "-m", "walltime", "--repeat", "2"])
+            .args(["--save-baseline", "repeated"])
+            .env("CODSPEED_ENV", "local")
+            .env("CODSPEED_KEEP_RAW_SAMPLES", keep_raw_samples)
+            .assert()
+            .success();
+        let results: Value = serde_json::from_str(
+            &fs::read_to_string(Path::new(&dir).join("target/codspeed/baselines/repeated.json"))
+                .unwrap(),
+        )
+        .unwrap();
+        results["benchmarks"][0].get("raw_samples").cloned()
+    };
+    assert!(raw_samples("true").is_some());
+    assert!(raw_samples("false").is_none());
+    teardown(dir);
+}
+
+#[test]
+fn test_repeat_requires_walltime() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "simulation", "--repeat", "2"])
+        .assert()
+        .failure()
+        .stderr(contains("--repeat is only supported in walltime mode"));
+    teardown(dir);
+}
@@ -0,0 +1,51 @@
+use predicates::str::contains;
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/workspace.in";
+
+/// Runs a shard, returning the suites it ran
+fn run_shard(dir: &String, shard: &str) -> Vec<String> {
+    let output = cargo_codspeed(dir)
+        .args(["run", "--shard", shard])
+        .assert()
+        .success()
+        .stderr(contains(format!("benchmark suite(s) for shard {shard}")));
+    ran_suites(&output.get_output().stderr)
+}
+
+#[test]
+fn test_run_shards() {
+    let dir = setup(DIR, Project::Workspace);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    let output = cargo_codspeed(&dir).arg("run").assert().success();
+    let mut all_suites = ran_suites(&output.get_output().stderr);
+
+    // Every suite is run by exactly one shard, and always by the same one
+    let mut sharded_suites = run_shard(&dir, "1/2");
+    sharded_suites.extend(run_shard(&dir, "2/2"));
+    assert_eq!(run_shard(&dir, "1/2"), run_shard(&dir, "1/2"));
+    all_suites.sort();
+    sharded_suites.sort();
+    assert_eq!(all_suites, sharded_suites);
+    teardown(dir);
+}
+
+#[test]
+fn test_invalid_shard() {
+    let dir = setup(DIR, Project::Workspace);
+    cargo_codspeed(&dir)
+        .args(["run", "--shard", "3/2"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Invalid shard `3/2`, the index must be between 1 and 2",
+        ));
+    cargo_codspeed(&dir)
+        .args(["run", "--shard", "1"])
+        .assert()
+        .failure()
+        .stderr(contains("expected `<index>/<total>`"));
+    teardown(dir);
+}
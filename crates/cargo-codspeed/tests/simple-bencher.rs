@@ -71,6 +71,38 @@ fn test_simple_build_and_run_single() {
     teardown(dir);
 }
 
+#[test]
+fn test_simple_list() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["list", "--bench", "bencher_example"])
+        .assert()
+        .success()
+        .stdout(contains("bencher_example.rs::a"))
+        .stderr(contains("Found 2 benchmark(s) in 1 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_simple_list_filters() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["list", "--bench", "bencher_example", "--skip", "rs::a"])
+        .assert()
+        .success()
+        .stdout(contains("bencher_example.rs::b"))
+        .stderr(contains("Found 1 benchmark(s) in 1 benchmark suite(s)"));
+    cargo_codspeed(&dir)
+        .args(["list", "--bench", "bencher_example", "rs::a"])
+        .assert()
+        .success()
+        .stdout(contains("bencher_example.rs::a"))
+        .stderr(contains("Found 1 benchmark(s) in 1 benchmark suite(s)"));
+    teardown(dir);
+}
+
 #[test]
 fn test_simple_cargo_bench_no_run() {
     let dir = setup(DIR, Project::Simple);
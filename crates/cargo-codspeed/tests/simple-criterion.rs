@@ -42,6 +42,52 @@ fn test_criterion_build_and_run() {
     teardown(dir);
 }
 
+#[test]
+fn test_criterion_build_and_run_parallel() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["run", "--jobs", "2"])
+        .assert()
+        .success()
+        .stdout(contains(FIB_BENCH_NAME))
+        .stdout(contains(BUBBLE_SORT_BENCH_NAME))
+        .stderr(contains("Running up to 2 benchmark suites in parallel"))
+        .stderr(contains("Finished running 2 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_criterion_run_fail_fast() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    // Unknown arguments make the benchmark processes fail
+    cargo_codspeed(&dir)
+        .args(["run", "--", "--unknown-flag"])
+        .assert()
+        .code(2)
+        .stderr(contains("Done running").not())
+        .stderr(contains("Finished running").not());
+    teardown(dir);
+}
+
+#[test]
+fn test_criterion_run_no_fail_fast() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["run", "--no-fail-fast", "--", "--unknown-flag"])
+        .assert()
+        .code(2)
+        .stderr(contains("Finished running 2 benchmark suite(s)"))
+        .stderr(contains("2 benchmark suite(s) failed:"))
+        .stderr(contains("simple-criterion criterion_example: exit code 2"))
+        .stderr(contains(
+            "simple-criterion another_criterion_example: exit code 2",
+        ));
+    teardown(dir);
+}
+
 #[test]
 fn test_criterion_build_single() {
     let dir = setup(DIR, Project::Simple);
@@ -139,6 +185,88 @@ fn test_criterion_walltime_run_forwards_args_after_double_dash() {
     teardown(dir);
 }
 
+#[test]
+fn test_criterion_walltime_bench_filters_on_ids() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+    walltime_bench(&dir, "simple-criterion", "criterion_example")
+        .args(["--list", "--exact", FIB_BENCH_NAME])
+        .assert()
+        .success()
+        .stdout(contains(format!("{FIB_BENCH_NAME}: benchmark")));
+    walltime_bench(&dir, "simple-criterion", "criterion_example")
+        .args(["--list", "--skip", "fib"])
+        .assert()
+        .success()
+        .stdout(contains(FIB_BENCH_NAME).not());
+    teardown(dir);
+}
+
+#[test]
+fn test_criterion_walltime_run_skip() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--skip", "bubble"])
+        .assert()
+        .success()
+        .stdout(contains(FIB_BENCH_NAME))
+        .stdout(contains(BUBBLE_SORT_BENCH_NAME).not());
+    teardown(dir);
+}
+
+#[test]
+fn test_criterion_walltime_run_keep_raw_samples() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--keep-raw-samples"])
+        .args(["--save-baseline", "raw"])
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success();
+    let results =
+        std::fs::read_to_string(format!("{dir}/target/codspeed/baselines/raw.json")).unwrap();
+    assert!(results.contains("\"raw_samples\""));
+    assert!(results.contains("\"times_per_round_ns\""));
+    teardown(dir);
+}
+
+#[test]
+fn test_criterion_walltime_custom_target_dir() {
+    let dir = setup(DIR, Project::Simple);
+    let target_dir = format!("{dir}/custom-target");
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime"])
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .env("CODSPEED_ENV", "local")
+        .env_remove("CODSPEED_PROFILE_FOLDER")
+        .assert()
+        .success()
+        .stderr(contains("No walltime benchmarks found").not());
+    let results = std::fs::read_dir(format!("{target_dir}/codspeed/profiles/results"))
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].contains(FIB_BENCH_NAME));
+    teardown(dir);
+}
+
 #[test]
 fn test_criterion_cargo_bench_no_run() {
     let dir = setup(DIR, Project::Simple);
@@ -121,6 +121,87 @@ fn test_divan_build_and_run_filtered_by_name_single() {
     teardown(dir);
 }
 
+#[test]
+fn test_divan_list() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["list", "fib"])
+        .assert()
+        .success()
+        .stdout(contains(FIB_BENCH_NAME))
+        .stdout(contains(BUBBLE_SORT_BENCH_NAME).not());
+    teardown(dir);
+}
+
+#[test]
+fn test_divan_list_skip() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["list", "--skip", "fib"])
+        .assert()
+        .success()
+        .stdout(contains(FIB_BENCH_NAME).not())
+        .stdout(contains(BUBBLE_SORT_BENCH_NAME));
+    teardown(dir);
+}
+
+#[test]
+fn test_divan_list_walltime() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "simulation,walltime"])
+        .assert()
+        .success();
+    let list = |mode: &str| {
+        let output = cargo_codspeed(&dir)
+            .args(["list", "-m", mode])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let walltime_list = list("walltime");
+    assert!(walltime_list.contains(FIB_BENCH_NAME));
+    assert_eq!(list("simulation"), walltime_list);
+
+    // Both harnesses match the filters on the URIs
+    let fib_uri = walltime_list
+        .lines()
+        .find(|uri| uri.contains(FIB_BENCH_NAME))
+        .unwrap();
+    for mode in ["simulation", "walltime"] {
+        cargo_codspeed(&dir)
+            .args(["list", "-m", mode, "--exact", fib_uri])
+            .assert()
+            .success()
+            .stdout(contains(FIB_BENCH_NAME))
+            .stdout(contains(BUBBLE_SORT_BENCH_NAME).not());
+    }
+    teardown(dir);
+}
+
+#[test]
+fn test_divan_walltime_bench_filters_on_paths() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+    walltime_bench(&dir, "simple-divan", "divan_example")
+        .args(["--list", "--exact", "divan_example::fib_20"])
+        .assert()
+        .success()
+        .stdout(contains(FIB_BENCH_NAME));
+    walltime_bench(&dir, "simple-divan", "divan_example")
+        .args(["--list", "--skip", "divan_example::fib_20"])
+        .assert()
+        .success()
+        .stdout(contains(FIB_BENCH_NAME).not());
+    teardown(dir);
+}
+
 #[test]
 fn test_divan_cargo_bench_no_run() {
     let dir = setup(DIR, Project::Simple);
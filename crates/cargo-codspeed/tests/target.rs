@@ -0,0 +1,85 @@
+use predicates::str::contains;
+use std::{fs, path::Path, process::Command};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+fn host_triple() -> String {
+    let output = Command::new("rustc").arg("-vV").output().unwrap();
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_build_and_run_with_target() {
+    let dir = setup(DIR, Project::Simple);
+    let target = host_triple();
+    cargo_codspeed(&dir)
+        .args(["build", "--target", &target])
+        .assert()
+        .success();
+    assert!(Path::new(&dir)
+        .join("target/codspeed/analysis")
+        .join(&target)
+        .join("bench/simple-criterion/criterion_example")
+        .exists());
+
+    cargo_codspeed(&dir)
+        .arg("run")
+        .assert()
+        .failure()
+        .stderr(contains("No benchmarks found"));
+    cargo_codspeed(&dir)
+        .args(["run", "--target", &target])
+        .assert()
+        .success()
+        .stderr(contains("Finished running 2 benchmark suite(s)"));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_with_target_runner() {
+    let dir = setup(DIR, Project::Simple);
+    let target = host_triple();
+    fs::create_dir_all(Path::new(&dir).join(".cargo")).unwrap();
+    fs::write(
+        Path::new(&dir).join(".cargo/config.toml"),
+        format!(
+            "[target.{target}]\nrunner = [\"sh\", \"-c\", \"echo running through the runner >&2; exec \\\"$0\\\" \\\"$@\\\"\"]\n"
+        ),
+    )
+    .unwrap();
+    cargo_codspeed(&dir)
+        .args(["build", "--target", &target, "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "--target", &target])
+        .assert()
+        .success()
+        .stderr(contains("running through the runner"))
+        .stdout(contains("fib 20"));
+
+    // A runner given as a string is split like `--wrapper`, following the quoting rules of a shell
+    let runner_env_var = format!(
+        "CARGO_TARGET_{}_RUNNER",
+        target.to_uppercase().replace(['-', '.'], "_")
+    );
+    cargo_codspeed(&dir)
+        .args(["run", "--target", &target])
+        .env(
+            runner_env_var,
+            "sh -c 'echo running through the quoted runner >&2; exec \"$0\" \"$@\"'",
+        )
+        .assert()
+        .success()
+        .stderr(contains("running through the quoted runner"))
+        .stdout(contains("fib 20"));
+    teardown(dir);
+}
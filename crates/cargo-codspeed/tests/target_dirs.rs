@@ -0,0 +1,64 @@
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::path::Path;
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_build_modes_use_separate_target_dirs() {
+    let dir = setup(DIR, Project::Simple);
+    append_to_manifest(
+        &dir,
+        "[package.metadata.codspeed]\nseparate-target-dirs = true\n",
+    );
+    for mode in ["simulation", "walltime"] {
+        cargo_codspeed(&dir)
+            .args(["build", "-m", mode])
+            .assert()
+            .success()
+            .stderr(contains("Compiling serde_json"));
+    }
+    assert!(Path::new(&dir)
+        .join("target/codspeed/build/analysis")
+        .exists());
+    assert!(Path::new(&dir)
+        .join("target/codspeed/build/walltime")
+        .exists());
+
+    // Switching back to a previously built mode does not rebuild the dependencies
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "simulation"])
+        .assert()
+        .success()
+        .stderr(contains("Compiling serde_json").not());
+    cargo_codspeed(&dir).arg("run").assert().success();
+    teardown(dir);
+}
+
+#[test]
+fn test_separate_target_dirs_flag() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime", "--separate-target-dirs"])
+        .assert()
+        .success();
+    assert!(Path::new(&dir)
+        .join("target/codspeed/build/walltime")
+        .exists());
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime"])
+        .assert()
+        .success();
+    teardown(dir);
+}
+
+#[test]
+fn test_shared_target_dir_by_default() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir).arg("build").assert().success();
+    assert!(!Path::new(&dir).join("target/codspeed/build").exists());
+    cargo_codspeed(&dir).arg("run").assert().success();
+    teardown(dir);
+}
@@ -0,0 +1,181 @@
+use predicates::str::contains;
+use std::{fs, path::Path};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+/// Adds a `hanging` bench target, sleeping longer than any test timeout
+fn add_hanging_bench(dir: &str, manifest_extra: &str) {
+    let dir = Path::new(dir);
+    fs::write(
+        dir.join("benches/hanging.rs"),
+        r#"use criterion::{criterion_group, criterion_main, Criterion};
+
+fn hanging(c: &mut Criterion) {
+    c.bench_function("hanging", |b| {
+        b.iter(|| std::thread::sleep(std::time::Duration::from_secs(600)))
+    });
+}
+
+criterion_group!(benches, hanging);
+criterion_main!(benches);
+"#,
+    )
+    .unwrap();
+    append_to_manifest(
+        dir.to_str().unwrap(),
+        &format!("[[bench]]\nname = \"hanging\"\nharness = false\n{manifest_extra}"),
+    );
+}
+
+#[test]
+fn test_run_timeout() {
+    let dir = setup(DIR, Project::Simple);
+    add_hanging_bench(&dir, "");
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["run", "--timeout", "1s", "--bench", "hanging"])
+        .assert()
+        .code(124)
+        .stderr(contains(
+            "Benchmark suite simple-criterion hanging timed out after 1s and was killed",
+        ));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_timeout_no_fail_fast() {
+    let dir = setup(DIR, Project::Simple);
+    add_hanging_bench(&dir, "");
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["run", "--timeout", "1s", "--no-fail-fast"])
+        .assert()
+        .code(124)
+        .stderr(contains("Finished running 3 benchmark suite(s)"))
+        .stderr(contains("1 benchmark suite(s) failed:"))
+        .stderr(contains("simple-criterion hanging: timed out after 1s"));
+    teardown(dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_timeout_with_wrapper() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = setup(DIR, Project::Simple);
+    add_hanging_bench(&dir, "");
+    // Not using `exec`, so that the benchmark is not the direct child of cargo-codspeed
+    let wrapper = Path::new(&dir).join("wrapper.sh");
+    fs::write(&wrapper, "#!/bin/sh\n\"$@\"\nexit $?\n").unwrap();
+    fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+    cargo_codspeed(&dir).arg("build").assert().success();
+    // With several jobs, the output is piped and read until the benchmark exits
+    cargo_codspeed(&dir)
+        .args([
+            "run",
+            "--timeout",
+            "1s",
+            "--bench",
+            "hanging",
+            "--jobs",
+            "2",
+        ])
+        .arg("--wrapper")
+        .arg(&wrapper)
+        .timeout(std::time::Duration::from_secs(120))
+        .assert()
+        .code(124)
+        .stderr(contains("timed out after 1s"));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_package_metadata_timeout() {
+    let dir = setup(DIR, Project::Simple);
+    add_hanging_bench(&dir, "\n[package.metadata.codspeed]\ntimeout = \"500ms\"\n");
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["run", "--bench", "hanging", "--jobs", "2"])
+        .assert()
+        .code(124)
+        .stderr(contains("timed out after 500ms"));
+    teardown(dir);
+}
+
+#[test]
+fn test_run_timeout_overrides_package_metadata() {
+    let dir = setup(DIR, Project::Simple);
+    add_hanging_bench(&dir, "\n[package.metadata.codspeed]\ntimeout = \"1h\"\n");
+    cargo_codspeed(&dir).arg("build").assert().success();
+    cargo_codspeed(&dir)
+        .args(["run", "--timeout", "500ms", "--bench", "hanging"])
+        .assert()
+        .code(124)
+        .stderr(contains("timed out after 500ms"));
+    teardown(dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interrupted_run_with_timeout_kills_suite() {
+    use std::os::unix::{fs::PermissionsExt, process::ExitStatusExt};
+    use std::{process::Command, thread::sleep, time::Duration};
+
+    let dir = setup(DIR, Project::Simple);
+    add_hanging_bench(&dir, "\n[package.metadata.codspeed]\ntimeout = \"1h\"\n");
+    // Records the PID of the benchmark, `exec` keeping it the same
+    let pid_file = Path::new(&dir).join("bench.pid");
+    let wrapper = Path::new(&dir).join("wrapper.sh");
+    fs::write(
+        &wrapper,
+        format!("#!/bin/sh\necho $$ > {}\nexec \"$@\"\n", pid_file.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+    cargo_codspeed(&dir).arg("build").assert().success();
+
+    let mut cargo_codspeed = Command::new(assert_cmd::cargo::cargo_bin("cargo-codspeed"))
+        .args(["run", "--bench", "hanging", "--wrapper"])
+        .arg(&wrapper)
+        .current_dir(&dir)
+        .spawn()
+        .unwrap();
+    let bench_pid = (0..600)
+        .find_map(|_| {
+            sleep(Duration::from_millis(100));
+            fs::read_to_string(&pid_file)
+                .ok()
+                .filter(|pid| pid.ends_with('\n'))
+        })
+        .expect("The benchmark did not start")
+        .trim()
+        .to_string();
+    // Zombie processes are not running anymore, they are just not reaped yet
+    let is_running = |pid: &str| {
+        let output = Command::new("ps")
+            .args(["-o", "stat=", "-p", pid])
+            .output()
+            .unwrap();
+        output.status.success() && !String::from_utf8_lossy(&output.stdout).starts_with('Z')
+    };
+    assert!(is_running(&bench_pid));
+
+    Command::new("kill")
+        .args(["-INT", &cargo_codspeed.id().to_string()])
+        .status()
+        .unwrap();
+    let status = cargo_codspeed.wait().unwrap();
+    assert_eq!(status.signal(), Some(libc::SIGINT));
+    // The benchmark is killed along with cargo-codspeed, instead of being left running
+    assert!(
+        (0..100).any(|_| {
+            sleep(Duration::from_millis(100));
+            !is_running(&bench_pid)
+        }),
+        "The benchmark is still running"
+    );
+    teardown(dir);
+}
@@ -0,0 +1,59 @@
+use predicates::str::contains;
+use std::path::PathBuf;
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_save_and_compare_baseline() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "-m", "walltime"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--save-baseline", "main"])
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .stderr(contains("Saving baseline `main`"));
+    assert!(PathBuf::from(&dir)
+        .join("target/codspeed/baselines/main.json")
+        .exists());
+
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--baseline", "main"])
+        .env("CODSPEED_ENV", "local")
+        .assert()
+        .success()
+        .stderr(contains("Comparing against baseline `main`"))
+        .stdout(contains("Verdict"))
+        .stdout(contains("fib 20"));
+    teardown(dir);
+}
+
+#[test]
+fn test_missing_baseline() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "walltime", "--baseline", "missing"])
+        .assert()
+        .failure()
+        .stderr(contains("Failed to open walltime results"));
+    teardown(dir);
+}
+
+#[test]
+fn test_baseline_requires_walltime() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["run", "-m", "simulation", "--save-baseline", "main"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "--save-baseline and --baseline are only supported in walltime mode",
+        ));
+    teardown(dir);
+}
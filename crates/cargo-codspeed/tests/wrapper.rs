@@ -0,0 +1,48 @@
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+mod helpers;
+use helpers::*;
+
+const DIR: &str = "tests/simple-criterion.in";
+
+#[test]
+fn test_run_with_wrapper() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["build", "--bench", "criterion_example"])
+        .assert()
+        .success();
+    cargo_codspeed(&dir)
+        .args(["run", "--wrapper", "nice -n 5"])
+        .assert()
+        .success()
+        .stdout(contains("fib 20"));
+    // The wrapper receives the benchmark executable as its first argument
+    cargo_codspeed(&dir)
+        .args(["run", "--wrapper", "echo wrapped"])
+        .assert()
+        .success()
+        .stdout(contains("wrapped "))
+        .stdout(contains("simple-criterion/criterion_example"))
+        .stdout(contains("fib 20").not());
+    // Quoted arguments are kept together
+    cargo_codspeed(&dir)
+        .args(["run", "--wrapper", r#"sh -c 'echo "wrapped  twice" "$0"'"#])
+        .assert()
+        .success()
+        .stdout(contains("wrapped  twice "))
+        .stdout(contains("simple-criterion/criterion_example"))
+        .stdout(contains("fib 20").not());
+    teardown(dir);
+}
+
+#[test]
+fn test_run_with_invalid_wrapper() {
+    let dir = setup(DIR, Project::Simple);
+    cargo_codspeed(&dir)
+        .args(["run", "--wrapper", "sh -c 'echo"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid wrapper `sh -c 'echo`, unbalanced quotes"));
+    teardown(dir);
+}
@@ -25,11 +25,29 @@ impl InstrumentationStatus {
     }
 }
 
+/// Format of the messages printed for each benchmark, set through `CODSPEED_MESSAGE_FORMAT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    Human,
+    /// One JSON object per line, parsed by `cargo codspeed run --message-format json`
+    Json,
+}
+
+impl MessageFormat {
+    pub fn from_env() -> Self {
+        match std::env::var("CODSPEED_MESSAGE_FORMAT").as_deref() {
+            Ok("json") => MessageFormat::Json,
+            _ => MessageFormat::Human,
+        }
+    }
+}
+
 pub struct CodSpeed {
     benchmarked: Vec<String>,
     current_benchmark: CString,
     group_stack: Vec<String>,
     instrumentation_status: InstrumentationStatus,
+    message_format: MessageFormat,
 }
 
 impl CodSpeed {
@@ -67,6 +85,7 @@ impl CodSpeed {
             current_benchmark: CString::new("").expect("CString::new failed"),
             group_stack: Vec::new(),
             instrumentation_status,
+            message_format: MessageFormat::from_env(),
         }
     }
 
@@ -114,17 +133,55 @@ impl CodSpeed {
             "Checked"
         };
 
-        let group_str = if self.group_stack.is_empty() {
-            "".to_string()
-        } else {
-            format!(" (group: {})", self.group_stack.join("/"))
-        };
-        println!(
-            "{}: {}{}",
-            action_str,
-            self.current_benchmark.to_string_lossy(),
-            group_str
-        );
+        match self.message_format {
+            MessageFormat::Human => {
+                let group_str = if self.group_stack.is_empty() {
+                    "".to_string()
+                } else {
+                    format!(" (group: {})", self.group_stack.join("/"))
+                };
+                println!(
+                    "{}: {}{}",
+                    action_str,
+                    self.current_benchmark.to_string_lossy(),
+                    group_str
+                );
+            }
+            MessageFormat::Json => {
+                let group = (!self.group_stack.is_empty()).then(|| self.group_stack.join("/"));
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "reason": format!("benchmark-{}", action_str.to_lowercase()),
+                        "uri": self.current_benchmark.to_string_lossy(),
+                        "group": group,
+                    })
+                );
+            }
+        }
+    }
+
+    /// Reports a benchmark that will not be run, e.g. because it is ignored or unsupported
+    pub fn skip_benchmark(&mut self, uri: &str, reason: &str) {
+        match self.message_format {
+            MessageFormat::Human => {
+                println!(
+                    "{} {} ({reason})",
+                    "Skipping:".to_string().yellow(),
+                    uri.yellow(),
+                );
+            }
+            MessageFormat::Json => {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "reason": "benchmark-skipped",
+                        "uri": uri,
+                        "message": reason,
+                    })
+                );
+            }
+        }
     }
 }
 
@@ -73,10 +73,23 @@ pub fn running_with_codspeed_runner() -> bool {
     std::env::var("CODSPEED_ENV").is_ok()
 }
 
+/// Whether the benchmark was started by `cargo codspeed run` or the CodSpeed runner
+pub fn running_with_cargo_codspeed() -> bool {
+    running_with_codspeed_runner() || std::env::var("CODSPEED_CARGO_WORKSPACE_ROOT").is_ok()
+}
+
 pub fn is_perf_enabled() -> bool {
     std::env::var("CODSPEED_PERF_ENABLED").is_ok()
 }
 
+/// Whether `CODSPEED_KEEP_RAW_SAMPLES` is set to `true` or `1`
+pub fn keep_raw_samples() -> bool {
+    matches!(
+        std::env::var("CODSPEED_KEEP_RAW_SAMPLES").as_deref(),
+        Ok("true" | "1")
+    )
+}
+
 /// Generate a statistically unique ID in a format resembling UUID v4.
 pub fn generate_unique_id() -> String {
     // Generate random bytes for UUID v4
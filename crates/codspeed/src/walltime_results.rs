@@ -12,6 +12,15 @@ use crate::utils::generate_unique_id;
 const IQR_OUTLIER_FACTOR: f64 = 1.5;
 const STDEV_OUTLIER_FACTOR: f64 = 3.0;
 
+/// Minimum number of rounds needed to tell warmup rounds apart from the steady state
+const WARMUP_DETECTION_MIN_ROUNDS: usize = 10;
+/// Number of (normal-consistent) MADs above the steady state median for a round to be warmup
+const WARMUP_MAD_FACTOR: f64 = 3.0;
+/// Minimum relative slowdown compared to the steady state median for a round to be warmup
+const WARMUP_MIN_RELATIVE_SLOWDOWN: f64 = 0.05;
+/// Scale factor making the MAD a consistent estimator of the standard deviation
+const MAD_NORMAL_CONSISTENCY: f64 = 1.4826;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BenchmarkMetadata {
     pub name: String,
@@ -33,7 +42,17 @@ struct BenchmarkStats {
     total_time: f64,
     iqr_outlier_rounds: u64,
     stdev_outlier_rounds: u64,
+    /// Average number of iterations per round
     iter_per_round: u64,
+    /// Number of iterations of each round, which varies with criterion's linear sampling mode.
+    /// Only kept along with the raw samples, since it is as large.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    iters_per_round: Vec<u64>,
+    /// Time per iteration estimated with a least-squares linear regression of the round times
+    /// over the iteration counts
+    #[serde(default)]
+    slope_ns: f64,
+    /// Number of iterations in the leading rounds detected as warmup, excluded from the stats
     warmup_iters: u64,
 }
 
@@ -45,6 +64,13 @@ struct BenchmarkConfig {
     max_rounds: Option<u64>,
 }
 
+/// Raw rounds as reported by the integration, including the warmup rounds
+#[derive(Debug, Serialize, Deserialize)]
+struct RawSamples {
+    iters_per_round: Vec<u128>,
+    times_per_round_ns: Vec<u128>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalltimeBenchmark {
     #[serde(flatten)]
@@ -52,12 +78,18 @@ pub struct WalltimeBenchmark {
 
     config: BenchmarkConfig,
     stats: BenchmarkStats,
+
+    /// Only kept when `CODSPEED_KEEP_RAW_SAMPLES` is `true`, since they can be large
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    raw_samples: Option<RawSamples>,
 }
 
 impl WalltimeBenchmark {
     /// Entry point called in patched integration to harvest raw walltime data
     ///
-    /// `CODSPEED_CARGO_WORKSPACE_ROOT` is expected to be set for this to work
+    /// `CODSPEED_CARGO_WORKSPACE_ROOT` is expected to be set for this to work. The results are
+    /// written in `CODSPEED_CARGO_TARGET_DIR` if set, or in `{workspace_root}/target` otherwise.
+    /// The raw rounds are also written to the results when `CODSPEED_KEEP_RAW_SAMPLES` is `true`.
     ///
     /// # Arguments
     ///
@@ -98,32 +130,122 @@ impl WalltimeBenchmark {
             eprintln!("codspeed failed to get workspace root. skipping");
             return;
         };
-        let data = WalltimeBenchmark::from_runtime_data(
+        // Fallback for older versions of cargo-codspeed, which do not forward the target dir
+        let target_dir = std::env::var("CODSPEED_CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| workspace_root.join("target"));
+        let raw_samples = crate::utils::keep_raw_samples().then(|| RawSamples {
+            iters_per_round: iters_per_round.clone(),
+            times_per_round_ns: times_per_round_ns.clone(),
+        });
+        let mut data = WalltimeBenchmark::from_runtime_data(
             name,
             uri,
             iters_per_round,
             times_per_round_ns,
             max_time_ns,
         );
-        data.dump_to_results(&workspace_root, scope);
+        data.set_raw_samples(raw_samples);
+        data.dump_to_results(&target_dir, scope);
     }
 
+    /// Computes the benchmark stats from the raw rounds.
+    ///
+    /// Leading rounds detected as warmup are excluded from all the stats but `total_time`, and
+    /// their iterations are counted in `warmup_iters`.
     pub fn from_runtime_data(
         name: String,
         uri: String,
-        iters_per_round: Vec<u128>,
-        times_per_round_ns: Vec<u128>,
+        mut iters_per_round: Vec<u128>,
+        mut times_per_round_ns: Vec<u128>,
         max_time_ns: Option<u128>,
     ) -> Self {
         let total_time = times_per_round_ns.iter().sum::<u128>() as f64 / 1_000_000_000.0;
-        let time_per_iteration_per_round_ns: Vec<_> = times_per_round_ns
-            .into_iter()
-            .zip(&iters_per_round)
-            .map(|(time_per_round, iter_per_round)| time_per_round / iter_per_round)
-            .map(|t| t as f64)
-            .collect::<Vec<f64>>();
+        let warmup_rounds = detect_warmup_rounds(&iters_per_round, &times_per_round_ns);
+        let warmup_iters = iters_per_round.drain(..warmup_rounds).sum::<u128>() as u64;
+        times_per_round_ns.drain(..warmup_rounds);
+
+        let mut benchmark = Self::from_steady_rounds(
+            BenchmarkMetadata { name, uri },
+            iters_per_round,
+            times_per_round_ns,
+            total_time,
+            warmup_iters,
+        );
+        benchmark.config.max_time_ns = max_time_ns.map(|t| t as f64);
+        benchmark
+    }
 
-        let mut data = Data::new(time_per_iteration_per_round_ns);
+    /// Merges the runs of a benchmark measured by several processes, e.g. with
+    /// `cargo codspeed run --repeat`, into a single benchmark.
+    ///
+    /// Warmup rounds are detected in each run separately, and the stats are then computed over
+    /// the steady rounds of all the runs. Returns `None` if the raw samples of a run were not kept.
+    pub fn merge_runs(runs: Vec<WalltimeBenchmark>, keep_raw_samples: bool) -> Option<Self> {
+        let mut runs = runs.into_iter();
+        let first = runs.next()?;
+        let mut all_iters_per_round = vec![];
+        let mut all_times_per_round_ns = vec![];
+        let mut iters_per_round = vec![];
+        let mut times_per_round_ns = vec![];
+        let mut total_time = 0.0;
+        let mut warmup_iters = 0;
+        let (metadata, config) = (first.metadata, first.config);
+        for raw_samples in std::iter::once(first.raw_samples).chain(runs.map(|r| r.raw_samples)) {
+            let raw_samples = raw_samples?;
+            let warmup_rounds = detect_warmup_rounds(
+                &raw_samples.iters_per_round,
+                &raw_samples.times_per_round_ns,
+            );
+            total_time +=
+                raw_samples.times_per_round_ns.iter().sum::<u128>() as f64 / 1_000_000_000.0;
+            warmup_iters += raw_samples.iters_per_round[..warmup_rounds]
+                .iter()
+                .sum::<u128>() as u64;
+            iters_per_round.extend(&raw_samples.iters_per_round[warmup_rounds..]);
+            times_per_round_ns.extend(&raw_samples.times_per_round_ns[warmup_rounds..]);
+            all_iters_per_round.extend(raw_samples.iters_per_round);
+            all_times_per_round_ns.extend(raw_samples.times_per_round_ns);
+        }
+
+        let mut benchmark = Self::from_steady_rounds(
+            metadata,
+            iters_per_round,
+            times_per_round_ns,
+            total_time,
+            warmup_iters,
+        );
+        benchmark.config = config;
+        benchmark.set_raw_samples(keep_raw_samples.then_some(RawSamples {
+            iters_per_round: all_iters_per_round,
+            times_per_round_ns: all_times_per_round_ns,
+        }));
+        Some(benchmark)
+    }
+
+    /// Sets the raw samples, and drops the per-round iteration counts of the stats if they are
+    /// not kept
+    fn set_raw_samples(&mut self, raw_samples: Option<RawSamples>) {
+        if raw_samples.is_none() {
+            self.stats.iters_per_round.clear();
+        }
+        self.raw_samples = raw_samples;
+    }
+
+    /// Computes the benchmark stats from rounds that do not include any warmup round
+    fn from_steady_rounds(
+        metadata: BenchmarkMetadata,
+        iters_per_round: Vec<u128>,
+        times_per_round_ns: Vec<u128>,
+        total_time: f64,
+        warmup_iters: u64,
+    ) -> Self {
+        let slope_ns = least_squares_slope(&iters_per_round, &times_per_round_ns);
+
+        let mut data = Data::new(time_per_iteration_per_round_ns(
+            &iters_per_round,
+            &times_per_round_ns,
+        ));
         let rounds = data.len() as u64;
 
         let mean_ns = data.mean().unwrap();
@@ -159,10 +281,9 @@ impl WalltimeBenchmark {
         let min_ns = data.min();
         let max_ns = data.max();
 
-        // TODO(COD-1056): We currently only support single iteration count per round
         let iter_per_round =
             (iters_per_round.iter().sum::<u128>() / iters_per_round.len() as u128) as u64;
-        let warmup_iters = 0; // FIXME: add warmup detection
+        let iters_per_round = iters_per_round.into_iter().map(|i| i as u64).collect();
 
         let stats = BenchmarkStats {
             min_ns,
@@ -177,21 +298,21 @@ impl WalltimeBenchmark {
             iqr_outlier_rounds,
             stdev_outlier_rounds,
             iter_per_round,
+            iters_per_round,
+            slope_ns,
             warmup_iters,
         };
 
         WalltimeBenchmark {
-            metadata: BenchmarkMetadata { name, uri },
-            config: BenchmarkConfig {
-                max_time_ns: max_time_ns.map(|t| t as f64),
-                ..Default::default()
-            },
+            metadata,
+            config: BenchmarkConfig::default(),
             stats,
+            raw_samples: None,
         }
     }
 
-    fn dump_to_results(&self, workspace_root: &Path, scope: &str) {
-        let output_dir = result_dir_from_workspace_root(workspace_root).join(scope);
+    fn dump_to_results(&self, target_dir: &Path, scope: &str) {
+        let output_dir = result_dir_from_target_dir(target_dir).join(scope);
         std::fs::create_dir_all(&output_dir).unwrap();
         let bench_id = generate_unique_id();
         let output_path = output_dir.join(format!("{bench_id}.json"));
@@ -207,6 +328,93 @@ impl WalltimeBenchmark {
     pub fn name(&self) -> &str {
         &self.metadata.name
     }
+
+    pub fn uri(&self) -> &str {
+        &self.metadata.uri
+    }
+
+    pub fn mean_ns(&self) -> f64 {
+        self.stats.mean_ns
+    }
+
+    pub fn median_ns(&self) -> f64 {
+        self.stats.median_ns
+    }
+
+    pub fn stdev_ns(&self) -> f64 {
+        self.stats.stdev_ns
+    }
+
+    pub fn slope_ns(&self) -> f64 {
+        self.stats.slope_ns
+    }
+
+    pub fn rounds(&self) -> u64 {
+        self.stats.rounds
+    }
+}
+
+fn time_per_iteration_per_round_ns(
+    iters_per_round: &[u128],
+    times_per_round_ns: &[u128],
+) -> Vec<f64> {
+    times_per_round_ns
+        .iter()
+        .zip(iters_per_round)
+        .map(|(time_per_round, iter_per_round)| (time_per_round / iter_per_round) as f64)
+        .collect()
+}
+
+/// Detects the number of leading rounds that are part of a warmup phase, e.g. while caches are
+/// filled or lazy statics are initialized.
+///
+/// The steady state is estimated with the median and the MAD of the second half of the rounds.
+/// Leading rounds are then considered as warmup until the first one that is not significantly
+/// slower than this steady state. At most the first half of the rounds can be detected as warmup.
+///
+/// Nothing is detected when the iteration count varies between rounds, e.g. with criterion's
+/// linear sampling: the fixed overhead of each round makes the leading rounds, which have the
+/// fewest iterations, slower per iteration without being part of a warmup.
+fn detect_warmup_rounds(iters_per_round: &[u128], times_per_round_ns: &[u128]) -> usize {
+    let rounds = iters_per_round.len();
+    if rounds < WARMUP_DETECTION_MIN_ROUNDS
+        || iters_per_round.windows(2).any(|pair| pair[0] != pair[1])
+    {
+        return 0;
+    }
+    let time_per_iteration_per_round_ns =
+        time_per_iteration_per_round_ns(iters_per_round, times_per_round_ns);
+    let (leading, steady) = time_per_iteration_per_round_ns.split_at(rounds / 2);
+
+    let steady_median = Data::new(steady.to_vec()).median();
+    let steady_mad = Data::new(
+        steady
+            .iter()
+            .map(|t| (t - steady_median).abs())
+            .collect::<Vec<_>>(),
+    )
+    .median();
+    let threshold = steady_median
+        + f64::max(
+            WARMUP_MAD_FACTOR * MAD_NORMAL_CONSISTENCY * steady_mad,
+            WARMUP_MIN_RELATIVE_SLOWDOWN * steady_median,
+        );
+
+    leading.iter().take_while(|&&t| t > threshold).count()
+}
+
+/// Fits `time = slope * iters` with the least-squares method, as done by criterion's analysis.
+///
+/// With a constant iteration count, this is equivalent to the mean time per iteration, but
+/// it is more accurate when the iteration count grows linearly between rounds since it
+/// weighs the longer rounds more.
+fn least_squares_slope(iters_per_round: &[u128], times_per_round_ns: &[u128]) -> f64 {
+    let (xy, x2) = iters_per_round
+        .iter()
+        .zip(times_per_round_ns)
+        .map(|(&iters, &time)| (iters as f64, time as f64))
+        .fold((0.0, 0.0), |(xy, x2), (x, y)| (xy + x * y, x2 + x * x));
+    xy / x2
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -230,13 +438,13 @@ pub struct WalltimeResults {
 }
 
 impl WalltimeResults {
-    pub fn collect_walltime_results(workspace_root: &Path) -> Result<Self> {
-        // retrieve data from `{workspace_root}/target/codspeed/raw_results/{scope}/*.json
+    /// Collects the raw results written by the benchmark processes, `target_dir` being the
+    /// cargo target directory forwarded to them through `CODSPEED_CARGO_TARGET_DIR`
+    pub fn collect_walltime_results(target_dir: &Path) -> Result<Self> {
+        // retrieve data from `{target_dir}/codspeed/raw_results/walltime/{scope}/*.json
         let benchmarks = glob::glob(&format!(
             "{}/**/*.json",
-            result_dir_from_workspace_root(workspace_root)
-                .to_str()
-                .unwrap(),
+            result_dir_from_target_dir(target_dir).to_str().unwrap(),
         ))?
         .map(|sample| -> Result<_> {
             let sample = sample?;
@@ -258,27 +466,51 @@ impl WalltimeResults {
         })
     }
 
-    pub fn clear(workspace_root: &Path) -> Result<()> {
-        let raw_results_dir = result_dir_from_workspace_root(workspace_root);
+    pub fn clear(target_dir: &Path) -> Result<()> {
+        let raw_results_dir = result_dir_from_target_dir(target_dir);
         std::fs::remove_dir_all(&raw_results_dir).ok(); // ignore errors when the directory does not exist
         std::fs::create_dir_all(&raw_results_dir)
             .context("Failed to create raw_results directory")?;
         Ok(())
     }
 
+    /// Merges the benchmarks with the same URI, which were run by several processes
+    ///
+    /// See [`WalltimeBenchmark::merge_runs`].
+    pub fn merge_runs(self, keep_raw_samples: bool) -> Result<Self> {
+        let mut runs_by_uri: Vec<(String, Vec<WalltimeBenchmark>)> = vec![];
+        for benchmark in self.benchmarks {
+            match runs_by_uri
+                .iter_mut()
+                .find(|(uri, _)| *uri == benchmark.metadata.uri)
+            {
+                Some((_, runs)) => runs.push(benchmark),
+                None => runs_by_uri.push((benchmark.metadata.uri.clone(), vec![benchmark])),
+            }
+        }
+        let benchmarks = runs_by_uri
+            .into_iter()
+            .map(|(uri, runs)| {
+                WalltimeBenchmark::merge_runs(runs, keep_raw_samples).with_context(|| {
+                    format!("Missing raw samples to merge the runs of benchmark {uri}")
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(WalltimeResults { benchmarks, ..self })
+    }
+
     pub fn benchmarks(&self) -> &[WalltimeBenchmark] {
         &self.benchmarks
     }
 }
 
-// FIXME: This assumes that the cargo target dir is `target`, and duplicates information with
-// `cargo-codspeed::helpers::get_codspeed_target_dir`
-fn result_dir_from_workspace_root(workspace_root: &Path) -> PathBuf {
-    workspace_root
-        .join("target")
+/// Directory of the raw results, outside of the build mode directories since these contain a
+/// directory per profile, see `cargo-codspeed::helpers::get_codspeed_target_dir`
+fn result_dir_from_target_dir(target_dir: &Path) -> PathBuf {
+    target_dir
         .join("codspeed")
-        .join("walltime")
         .join("raw_results")
+        .join("walltime")
 }
 
 #[cfg(test)]
@@ -324,5 +556,200 @@ mod tests {
             benchmark.stats.total_time,
             42. * total_rounds / 1_000_000_000.0
         );
+        assert_eq!(benchmark.stats.iters_per_round, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(benchmark.stats.slope_ns, 42.);
+    }
+
+    #[test]
+    fn test_parse_bench_with_linear_sampling() {
+        // The first round is dominated by a fixed overhead, which skews the mean of the
+        // per-iteration times but barely affects the slope
+        let benchmark = WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            vec![1, 10, 20],
+            vec![100, 500, 1000],
+            None,
+        );
+
+        assert_eq!(benchmark.stats.mean_ns, (100. + 50. + 50.) / 3.);
+        assert_eq!(benchmark.stats.slope_ns, (100. + 5_000. + 20_000.) / 501.);
+    }
+
+    #[test]
+    fn test_raw_samples_serialization() {
+        let mut benchmark = WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            vec![1, 2],
+            vec![42, 84],
+            None,
+        );
+        benchmark.set_raw_samples(Some(RawSamples {
+            iters_per_round: vec![1, 2],
+            times_per_round_ns: vec![42, 84],
+        }));
+        let json = serde_json::to_value(&benchmark).unwrap();
+        assert_eq!(json["stats"]["iters_per_round"], serde_json::json!([1, 2]));
+        assert_eq!(
+            json["raw_samples"],
+            serde_json::json!({"iters_per_round": [1, 2], "times_per_round_ns": [42, 84]})
+        );
+        let parsed: WalltimeBenchmark = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.raw_samples.unwrap().times_per_round_ns, vec![42, 84]);
+
+        benchmark.set_raw_samples(None);
+        let json = serde_json::to_value(&benchmark).unwrap();
+        assert!(json.get("raw_samples").is_none());
+        assert!(json["stats"].get("iters_per_round").is_none());
+    }
+
+    #[test]
+    fn test_warmup_rounds_are_dropped() {
+        let times_per_round_ns = [900, 400, 250]
+            .into_iter()
+            .chain((0..17).map(|i| 100 + i % 3))
+            .collect::<Vec<_>>();
+        let total_time = times_per_round_ns.iter().sum::<u128>() as f64 / 1_000_000_000.0;
+
+        let benchmark = WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            vec![2; 20],
+            times_per_round_ns.iter().map(|t| t * 2).collect(),
+            None,
+        );
+
+        assert_eq!(benchmark.stats.warmup_iters, 6);
+        assert_eq!(benchmark.stats.rounds, 17);
+        assert_eq!(benchmark.stats.iters_per_round.len(), 17);
+        assert_eq!(benchmark.stats.max_ns, 102.);
+        assert_eq!(benchmark.stats.total_time, total_time * 2.);
+    }
+
+    #[test]
+    fn test_no_warmup_detected_on_steady_rounds() {
+        let benchmark = WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            vec![1; 20],
+            (0..20).map(|i| 100 + i % 4).collect(),
+            None,
+        );
+
+        assert_eq!(benchmark.stats.warmup_iters, 0);
+        assert_eq!(benchmark.stats.rounds, 20);
+    }
+
+    #[test]
+    fn test_no_warmup_detected_with_linear_sampling() {
+        // A fixed overhead of 500ns per round makes the first rounds slower per iteration
+        let iters_per_round = (1..=20).map(|i| i * 10).collect::<Vec<_>>();
+        let times_per_round_ns = iters_per_round
+            .iter()
+            .map(|iters| 500 + iters * 100)
+            .collect();
+        let benchmark = WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            iters_per_round,
+            times_per_round_ns,
+            None,
+        );
+
+        assert_eq!(benchmark.stats.warmup_iters, 0);
+        assert_eq!(benchmark.stats.rounds, 20);
+    }
+
+    #[test]
+    fn test_warmup_detection_keeps_late_slow_rounds() {
+        // Only leading slow rounds are warmup, slow rounds afterwards are outliers
+        let benchmark = WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            vec![1; 20],
+            (0..20)
+                .map(|i| if i == 1 || i == 15 { 500 } else { 100 })
+                .collect(),
+            None,
+        );
+
+        assert_eq!(benchmark.stats.warmup_iters, 0);
+        assert_eq!(benchmark.stats.max_ns, 500.);
+    }
+
+    fn run_with_raw_samples(
+        iters_per_round: Vec<u128>,
+        times_per_round_ns: Vec<u128>,
+    ) -> WalltimeBenchmark {
+        let mut benchmark = WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            iters_per_round.clone(),
+            times_per_round_ns.clone(),
+            None,
+        );
+        benchmark.raw_samples = Some(RawSamples {
+            iters_per_round,
+            times_per_round_ns,
+        });
+        benchmark
+    }
+
+    #[test]
+    fn test_merge_runs() {
+        // The second run has two warmup rounds
+        let mut second_run_times = vec![500, 400];
+        second_run_times.extend([100; 10]);
+        let runs = vec![
+            run_with_raw_samples(vec![1; 10], vec![200; 10]),
+            run_with_raw_samples(vec![1; 12], second_run_times),
+        ];
+
+        let merged = WalltimeBenchmark::merge_runs(runs, false).unwrap();
+        assert_eq!(merged.stats.rounds, 20);
+        assert_eq!(merged.stats.warmup_iters, 2);
+        assert_eq!(merged.stats.median_ns, 150.);
+        assert_eq!(merged.stats.min_ns, 100.);
+        assert_eq!(merged.stats.max_ns, 200.);
+        assert_eq!(merged.stats.total_time, 3_900. / 1_000_000_000.0);
+        assert!(merged.raw_samples.is_none());
+    }
+
+    #[test]
+    fn test_merge_runs_keeps_all_raw_samples() {
+        let runs = vec![
+            run_with_raw_samples(vec![1, 2], vec![42, 84]),
+            run_with_raw_samples(vec![3], vec![126]),
+        ];
+
+        let merged = WalltimeBenchmark::merge_runs(runs, true).unwrap();
+        let raw_samples = merged.raw_samples.unwrap();
+        assert_eq!(raw_samples.iters_per_round, vec![1, 2, 3]);
+        assert_eq!(raw_samples.times_per_round_ns, vec![42, 84, 126]);
+    }
+
+    #[test]
+    fn test_merge_runs_without_raw_samples() {
+        let runs = vec![WalltimeBenchmark::from_runtime_data(
+            NAME.to_string(),
+            URI.to_string(),
+            vec![1],
+            vec![42],
+            None,
+        )];
+        assert!(WalltimeBenchmark::merge_runs(runs, false).is_none());
+    }
+
+    #[test]
+    fn test_clear_keeps_the_benchmarks_of_a_raw_results_profile() {
+        let target_dir = std::env::temp_dir().join(crate::utils::generate_unique_id());
+        let executable = target_dir.join("codspeed/walltime/raw_results/package/bench");
+        std::fs::create_dir_all(executable.parent().unwrap()).unwrap();
+        std::fs::write(&executable, "").unwrap();
+
+        WalltimeResults::clear(&target_dir).unwrap();
+        assert!(executable.exists());
+        std::fs::remove_dir_all(target_dir).unwrap();
     }
 }
@@ -262,7 +262,7 @@ pub(crate) fn common<M: Measurement, T: ?Sized>(
     }
 }
 
-mod codspeed {
+pub(crate) mod codspeed {
     use crate::{measurement::Measurement, report::BenchmarkId, Criterion};
 
     /// WARNING: Keep URI generation in sync with `codspeed-criterion-compat::compat::group::run_bench`
@@ -301,7 +301,11 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
         id.ensure_title_unique(&self.criterion.all_titles);
         self.criterion.all_titles.insert(id.as_title().to_owned());
 
-        let do_run = self.criterion.filter_matches(id.id());
+        // CodSpeed addition: when run by `cargo codspeed`, the filters also match the URI of the
+        // benchmark, as in the instrumented harness
+        let uri = ::codspeed::utils::running_with_cargo_codspeed()
+            .then(|| analysis::codspeed::create_uri_and_name(&id, self.criterion).0);
+        let do_run = self.criterion.filter_matches(id.id(), uri.as_deref());
         self.any_matched |= do_run;
         let mut func = Function::new(f);
 
@@ -330,7 +334,14 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
             }
             Mode::List(_) => {
                 if do_run {
-                    println!("{}: benchmark", id);
+                    // CodSpeed addition: list the URIs when run by CodSpeed, as the instrumented
+                    // harness does
+                    if ::codspeed::utils::running_with_codspeed_runner() {
+                        let (uri, _) = analysis::codspeed::create_uri_and_name(&id, self.criterion);
+                        println!("{}: benchmark", uri);
+                    } else {
+                        println!("{}: benchmark", id);
+                    }
                 }
             }
             Mode::Test => {
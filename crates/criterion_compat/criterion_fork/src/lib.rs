@@ -368,6 +368,7 @@ pub enum BenchmarkFilter {
 pub struct Criterion<M: Measurement = WallTime> {
     config: BenchmarkConfig,
     filter: BenchmarkFilter,
+    skip_filters: Vec<BenchmarkFilter>,
     report: Reports,
     output_directory: PathBuf,
     baseline_directory: String,
@@ -468,6 +469,7 @@ impl Default for Criterion {
                 quick_mode: false,
             },
             filter: BenchmarkFilter::AcceptAll,
+            skip_filters: vec![],
             report: reports,
             baseline_directory: "base".to_owned(),
             baseline: Baseline::Save,
@@ -504,6 +506,7 @@ impl<M: Measurement> Criterion<M> {
         Criterion {
             config: self.config,
             filter: self.filter,
+            skip_filters: self.skip_filters,
             report: self.report,
             baseline_directory: self.baseline_directory,
             baseline: self.baseline,
@@ -805,7 +808,7 @@ impl<M: Measurement> Criterion<M> {
     #[must_use]
     #[cfg_attr(feature = "cargo-clippy", allow(clippy::cognitive_complexity))]
     pub fn configure_from_args(mut self) -> Criterion<M> {
-        use clap::{value_parser, Arg, Command};
+        use clap::{value_parser, Arg, ArgAction, Command};
         let matches = Command::new("Criterion Benchmark")
             .arg(Arg::new("FILTER")
                 .help("Skip benchmarks whose names do not contain FILTER.")
@@ -867,6 +870,11 @@ impl<M: Measurement> Criterion<M> {
                 .long("ignored")
                 .num_args(0)
                 .help("List or run ignored benchmarks (currently means skip all benchmarks)"))
+            .arg(Arg::new("skip")
+                .long("skip")
+                .value_name("FILTER")
+                .action(ArgAction::Append)
+                .help("Skip benchmarks whose names match FILTER. Can be repeated."))
             .arg(Arg::new("exact")
                 .long("exact")
                 .num_args(0)
@@ -1035,10 +1043,7 @@ https://bheisler.github.io/criterion.rs/book/faq.html
             self.connection = None;
         }
 
-        let filter = if matches.get_flag("ignored") {
-            // --ignored overwrites any name-based filters passed in.
-            BenchmarkFilter::RejectAll
-        } else if let Some(filter) = matches.get_one::<String>("FILTER") {
+        let parse_filter = |filter: &String| {
             if matches.get_flag("exact") {
                 BenchmarkFilter::Exact(filter.to_owned())
             } else {
@@ -1050,9 +1055,18 @@ https://bheisler.github.io/criterion.rs/book/faq.html
                 });
                 BenchmarkFilter::Regex(regex)
             }
+        };
+        let filter = if matches.get_flag("ignored") {
+            // --ignored overwrites any name-based filters passed in.
+            BenchmarkFilter::RejectAll
+        } else if let Some(filter) = matches.get_one::<String>("FILTER") {
+            parse_filter(filter)
         } else {
             BenchmarkFilter::AcceptAll
         };
+        if let Some(skip_filters) = matches.get_many::<String>("skip") {
+            self.skip_filters = skip_filters.map(parse_filter).collect();
+        }
         self = self.with_benchmark_filter(filter);
 
         match matches.get_one("plotting-backend").map(String::as_str) {
@@ -1173,13 +1187,17 @@ https://bheisler.github.io/criterion.rs/book/faq.html
         self
     }
 
-    fn filter_matches(&self, id: &str) -> bool {
-        match &self.filter {
-            BenchmarkFilter::AcceptAll => true,
-            BenchmarkFilter::Regex(regex) => regex.is_match(id),
-            BenchmarkFilter::Exact(exact) => id == exact,
-            BenchmarkFilter::RejectAll => false,
-        }
+    /// CodSpeed addition: a filter matches either the id or, if any, the URI of the benchmark
+    fn filter_matches(&self, id: &str, uri: Option<&str>) -> bool {
+        let matches = |filter: &BenchmarkFilter| {
+            std::iter::once(id).chain(uri).any(|name| match filter {
+                BenchmarkFilter::AcceptAll => true,
+                BenchmarkFilter::Regex(regex) => regex.is_match(name),
+                BenchmarkFilter::Exact(exact) => name == exact,
+                BenchmarkFilter::RejectAll => false,
+            })
+        };
+        matches(&self.filter) && !self.skip_filters.iter().any(matches)
     }
 
     /// Returns true iff we should save the benchmark results in
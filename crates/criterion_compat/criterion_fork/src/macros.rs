@@ -68,7 +68,11 @@ macro_rules! criterion_group {
             let mut criterion: $crate::Criterion<_> = $config
                 .configure_from_args();
             $(
-                if std::env::var("CODSPEED_ENV").is_ok() {
+                // CodSpeed addition: the URIs are also needed to match the filters when the
+                // benchmarks are run by `cargo codspeed` outside of the CodSpeed runner
+                if std::env::var("CODSPEED_ENV").is_ok()
+                    || std::env::var("CODSPEED_CARGO_WORKSPACE_ROOT").is_ok()
+                {
                     criterion.set_current_file($crate::abs_file!());
                     criterion.set_macro_group(format!("{}::{}", stringify!($name), stringify!($target)));
                 }
@@ -41,11 +41,8 @@ impl<'a> Bencher<'a> {
     where
         R: FnMut(u64) -> MV,
     {
-        println!(
-            "{} {} (CodSpeed doesn't support custom iterations)",
-            "Skipping:".to_string().yellow(),
-            self.uri.yellow(),
-        );
+        self.codspeed
+            .skip_benchmark(&self.uri, "CodSpeed doesn't support custom iterations");
     }
 
     /// Run `routine` with a precise schedule: `opts.rounds` measurement rounds,
@@ -182,11 +179,8 @@ impl<'a, 'b, A: AsyncExecutor> AsyncBencher<'a, 'b, A> {
         F: Future<Output = MV>,
     {
         let AsyncBencher { b, .. } = self;
-        println!(
-            "{} {} (CodSpeed doesn't support custom iterations)",
-            "Skipping:".to_string().yellow(),
-            b.uri.yellow(),
-        );
+        b.codspeed
+            .skip_benchmark(&b.uri, "CodSpeed doesn't support custom iterations");
     }
 
     /// Async/await variant of [`Bencher::iter_manual_unstable`]. Bypasses
@@ -15,31 +15,40 @@ pub struct Criterion<M: Measurement = WallTime> {
     pub current_file: String,
     pub macro_group: String,
     pub filter: BenchmarkFilter,
+    /// Benchmarks matching any of these filters are skipped, with `--skip`
+    pub skip_filters: Vec<BenchmarkFilter>,
+    /// Only print the URIs of the benchmarks that would be run, with `--list`
+    pub list: bool,
     phantom: PhantomData<*const M>,
 }
 
 #[doc(hidden)]
 impl Criterion {
     pub fn new_instrumented() -> Self {
-        println!(
-            "Harness: codspeed-criterion-compat v{}",
-            env!("CARGO_PKG_VERSION"),
-        );
-
         // Parse CLI arguments to extract filter
-        let filter = Self::parse_filter();
+        let (filter, skip_filters, list) = Self::parse_args();
+
+        // Keep the output parsable when listing benchmarks
+        if !list {
+            println!(
+                "Harness: codspeed-criterion-compat v{}",
+                env!("CARGO_PKG_VERSION"),
+            );
+        }
 
         Criterion {
             codspeed: Some(Rc::new(RefCell::new(CodSpeed::new()))),
             current_file: String::new(),
             macro_group: String::new(),
             filter,
+            skip_filters,
+            list,
             phantom: PhantomData,
         }
     }
 
-    fn parse_filter() -> BenchmarkFilter {
-        use clap::{Arg, Command};
+    fn parse_args() -> (BenchmarkFilter, Vec<BenchmarkFilter>, bool) {
+        use clap::{Arg, ArgAction, Command};
 
         let matches = Command::new("Criterion Benchmark")
             .arg(
@@ -47,16 +56,31 @@ impl Criterion {
                     .help("Skip benchmarks whose names do not contain FILTER.")
                     .index(1),
             )
+            .arg(
+                Arg::new("skip")
+                    .long("skip")
+                    .value_name("FILTER")
+                    .action(ArgAction::Append)
+                    .help("Skip benchmarks whose names match FILTER, can be repeated"),
+            )
             .arg(
                 Arg::new("exact")
                     .long("exact")
                     .num_args(0)
                     .help("Run benchmarks that exactly match the provided filter"),
             )
+            .arg(
+                Arg::new("list")
+                    .long("list")
+                    .num_args(0)
+                    .help("List all benchmarks instead of running them"),
+            )
             .get_matches();
+        let list = matches.get_flag("list");
+        let exact = matches.get_flag("exact");
 
-        if let Some(filter) = matches.get_one::<String>("FILTER") {
-            if matches.get_flag("exact") {
+        let parse_filter = |filter: &String| {
+            if exact {
                 BenchmarkFilter::Exact(filter.to_owned())
             } else {
                 let regex = Regex::new(filter).unwrap_or_else(|err| {
@@ -65,9 +89,17 @@ impl Criterion {
                 });
                 BenchmarkFilter::Regex(regex)
             }
-        } else {
-            BenchmarkFilter::AcceptAll
-        }
+        };
+        let filter = matches
+            .get_one::<String>("FILTER")
+            .map(parse_filter)
+            .unwrap_or(BenchmarkFilter::AcceptAll);
+        let skip_filters = matches
+            .get_many::<String>("skip")
+            .map(|filters| filters.map(parse_filter).collect())
+            .unwrap_or_default();
+
+        (filter, skip_filters, list)
     }
 
     pub fn with_patched_measurement<M: Measurement>(&mut self, _: Criterion<M>) -> Criterion<M> {
@@ -76,6 +108,8 @@ impl Criterion {
             current_file: self.current_file.clone(),
             macro_group: self.macro_group.clone(),
             filter: self.filter.clone(),
+            skip_filters: self.skip_filters.clone(),
+            list: self.list,
             phantom: PhantomData,
         }
     }
@@ -149,6 +183,8 @@ impl Default for Criterion {
             current_file: String::new(),
             macro_group: String::new(),
             filter: BenchmarkFilter::AcceptAll,
+            skip_filters: vec![],
+            list: false,
             phantom: PhantomData,
         }
     }
@@ -162,6 +198,8 @@ impl<M: Measurement> Criterion<M> {
             current_file: self.current_file,
             macro_group: self.macro_group,
             filter: self.filter,
+            skip_filters: self.skip_filters,
+            list: self.list,
             phantom: PhantomData::<*const M2>,
         }
     }
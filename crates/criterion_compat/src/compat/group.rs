@@ -18,6 +18,8 @@ pub struct BenchmarkGroup<'a, M: Measurement = WallTime> {
     macro_group: String,
     group_name: String,
     filter: BenchmarkFilter,
+    skip_filters: Vec<BenchmarkFilter>,
+    list: bool,
     _marker: PhantomData<&'a M>,
 }
 
@@ -34,6 +36,8 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
             macro_group: criterion.macro_group.clone(),
             group_name,
             filter: criterion.filter.clone(),
+            skip_filters: criterion.skip_filters.clone(),
+            list: criterion.list,
             _marker: PhantomData,
         }
     }
@@ -78,8 +82,13 @@ impl<'a, M: Measurement> BenchmarkGroup<'a, M> {
             uri = format!("{uri}[{parameter}]");
         }
 
-        // Apply filter - skip benchmark if it doesn't match
-        if !self.filter.is_match(&uri) {
+        // Apply filters - skip benchmark if it doesn't match or is explicitly skipped
+        if !self.filter.is_match(&uri) || self.skip_filters.iter().any(|f| f.is_match(&uri)) {
+            return;
+        }
+
+        if self.list {
+            println!("{uri}: benchmark");
             return;
         }
 
@@ -79,16 +79,21 @@ impl Divan {
     /// Returns `true` if an entry at the given path should be considered for
     /// running.
     ///
+    /// CodSpeed addition: a filter matches either the path or, if any, the URI
+    /// of the benchmark, as in the instrumented harness.
+    ///
     /// This does not take into account `entry.ignored` because that is handled
     /// separately.
-    fn filter(&self, entry_path: &str) -> bool {
-        if !self.filters.is_empty()
-            && !self.filters.iter().any(|filter| filter.is_match(entry_path))
-        {
+    fn filter(&self, entry_path: &str, uri: Option<&str>) -> bool {
+        let is_match = |filter: &Filter| {
+            std::iter::once(entry_path).chain(uri).any(|name| filter.is_match(name))
+        };
+
+        if !self.filters.is_empty() && !self.filters.iter().any(is_match) {
             return false;
         }
 
-        !self.skip_filters.iter().any(|filter| filter.is_match(entry_path))
+        !self.skip_filters.iter().any(is_match)
     }
 
     pub(crate) fn should_ignore(&self, ignored: bool) -> bool {
@@ -124,7 +129,13 @@ impl Divan {
 
         // Filter after inserting groups so that we can properly use groups'
         // display names.
-        EntryTree::retain(&mut tree, |entry_path| self.filter(entry_path));
+        //
+        // CodSpeed addition: when run by `cargo codspeed`, also match the URIs.
+        let match_uris = ::codspeed::utils::running_with_cargo_codspeed();
+        EntryTree::retain(&mut tree, |entry_path, entry, display_name| {
+            let uri = match_uris.then(|| codspeed::name_and_uri(&entry, display_name).1);
+            self.filter(entry_path, uri.as_deref())
+        });
 
         // Quick exit without doing unnecessary work.
         if tree.is_empty() {
@@ -134,6 +145,12 @@ impl Divan {
         // Sorting is after filtering to compare fewer elements.
         EntryTree::sort_by_attr(&mut tree, self.sorting_attr, self.reverse_sort);
 
+        // CodSpeed addition: list the URIs when run by CodSpeed, as the instrumented harness does
+        if action.is_list() && ::codspeed::utils::running_with_codspeed_runner() {
+            self.list_tree(&tree, None);
+            return;
+        }
+
         let timer = match self.timer {
             TimerKind::Os => Timer::Os,
 
@@ -220,6 +237,58 @@ impl Divan {
         }
     }
 
+    fn list_tree(&self, tree: &[EntryTree], parent_options: Option<&BenchOptions>) {
+        for child in tree {
+            let child_options = child.bench_options();
+
+            // Overwrite `parent_options` with `child_options` if applicable.
+            let options: BenchOptions;
+            let options: Option<&BenchOptions> = match (parent_options, child_options) {
+                (None, None) => None,
+                (Some(options), None) | (None, Some(options)) => Some(options),
+                (Some(parent_options), Some(child_options)) => {
+                    options = child_options.overwrite(parent_options);
+                    Some(&options)
+                }
+            };
+
+            match child {
+                EntryTree::Leaf { entry, args } => {
+                    self.list_bench_entry(*entry, args.as_deref(), options)
+                }
+                EntryTree::Parent { children, .. } => self.list_tree(children, options),
+            }
+        }
+    }
+
+    fn list_bench_entry(
+        &self,
+        bench_entry: AnyBenchEntry,
+        bench_arg_names: Option<&[&&str]>,
+        entry_options: Option<&BenchOptions>,
+    ) {
+        let ignore = match entry_options {
+            None => self.bench_options.ignore,
+            Some(entry_options) => self.bench_options.overwrite(entry_options).ignore,
+        };
+        if self.should_ignore(ignore.unwrap_or_default()) {
+            return;
+        }
+
+        match bench_entry.bench_runner() {
+            BenchEntryRunner::Plain(_) => {
+                let (_, uri) = codspeed::name_and_uri(&bench_entry, bench_entry.display_name());
+                println!("{uri}: benchmark");
+            }
+            BenchEntryRunner::Args(_) => {
+                for arg_name in bench_arg_names.unwrap_or_default() {
+                    let (_, uri) = codspeed::name_and_uri(&bench_entry, arg_name);
+                    println!("{uri}: benchmark");
+                }
+            }
+        }
+    }
+
     fn run_bench_entry(
         &self,
         action: Action,
@@ -359,69 +428,75 @@ mod codspeed {
     use crate::bench::BenchContext;
     use crate::entry::AnyBenchEntry;
 
-    pub(crate) fn collect_walltime_results(
-        bench_context: &BenchContext,
+    /// Returns the name and URI of a benchmark
+    pub(crate) fn name_and_uri(
         bench_entry: &AnyBenchEntry,
         closure_bench_display_name: &str,
-    ) {
+    ) -> (String, String) {
         // WARNING: Keep URI generation in sync with `codspeed-divan-compat::uri::generate`
         // Not worth doing the work of actually using the same code since this fork is temporary
-        let (bench_name, uri) = {
-            let bench_function_name = bench_entry.meta().display_name;
+        let bench_function_name = bench_entry.meta().display_name;
 
-            let (bench_type_name, bench_arg_name) = {
-                let bench_function_or_type_name = bench_entry.display_name().to_string();
+        let (bench_type_name, bench_arg_name) = {
+            let bench_function_or_type_name = bench_entry.display_name().to_string();
 
-                let type_name = if bench_function_or_type_name == bench_function_name {
-                    None
-                } else {
-                    Some(bench_function_or_type_name)
-                };
-
-                let arg_name = match type_name.as_ref() {
-                    None => {
-                        if closure_bench_display_name == bench_function_name {
-                            None
-                        } else {
-                            Some(closure_bench_display_name)
-                        }
+            let type_name = if bench_function_or_type_name == bench_function_name {
+                None
+            } else {
+                Some(bench_function_or_type_name)
+            };
+
+            let arg_name = match type_name.as_ref() {
+                None => {
+                    if closure_bench_display_name == bench_function_name {
+                        None
+                    } else {
+                        Some(closure_bench_display_name)
                     }
-                    Some(type_name) => {
-                        if closure_bench_display_name == type_name {
-                            None
-                        } else {
-                            Some(closure_bench_display_name)
-                        }
+                }
+                Some(type_name) => {
+                    if closure_bench_display_name == type_name {
+                        None
+                    } else {
+                        Some(closure_bench_display_name)
                     }
-                };
-
-                (type_name, arg_name)
+                }
             };
 
-            let mut bench_name = bench_function_name.to_string();
+            (type_name, arg_name)
+        };
 
-            match (bench_type_name, bench_arg_name) {
-                (None, None) => {}
-                (Some(type_name), None) => {
-                    bench_name.push_str(format!("[{type_name}]").as_str());
-                }
-                (None, Some(arg_name)) => {
-                    bench_name.push_str(format!("[{arg_name}]").as_str());
-                }
-                (Some(type_name), Some(arg_name)) => {
-                    bench_name.push_str(format!("[{type_name}, {arg_name}]").as_str());
-                }
-            }
+        let mut bench_name = bench_function_name.to_string();
 
-            let file = bench_entry.meta().location.file;
-            let mut module_path =
-                bench_entry.meta().module_path_components().skip(1).collect::<Vec<_>>().join("::");
-            if !module_path.is_empty() {
-                module_path.push_str("::");
+        match (bench_type_name, bench_arg_name) {
+            (None, None) => {}
+            (Some(type_name), None) => {
+                bench_name.push_str(format!("[{type_name}]").as_str());
             }
-            let uri = format!("{file}::{module_path}{bench_name}");
-            (bench_name, uri)
-        };
+            (None, Some(arg_name)) => {
+                bench_name.push_str(format!("[{arg_name}]").as_str());
+            }
+            (Some(type_name), Some(arg_name)) => {
+                bench_name.push_str(format!("[{type_name}, {arg_name}]").as_str());
+            }
+        }
+
+        let file = bench_entry.meta().location.file;
+        let mut module_path =
+            bench_entry.meta().module_path_components().skip(1).collect::<Vec<_>>().join("::");
+        if !module_path.is_empty() {
+            module_path.push_str("::");
+        }
+        let uri = format!("{file}::{module_path}{bench_name}");
+        (bench_name, uri)
+    }
+
+    pub(crate) fn collect_walltime_results(
+        bench_context: &BenchContext,
+        bench_entry: &AnyBenchEntry,
+        closure_bench_display_name: &str,
+    ) {
+        let (bench_name, uri) = name_and_uri(bench_entry, closure_bench_display_name);
 
         let iter_per_round = bench_context.samples.sample_size;
         let iters_per_round =
@@ -158,11 +158,14 @@ impl<'a> EntryTree<'a> {
     }
 
     /// Removes entries from the tree whose paths do not match the filter.
-    pub fn retain(tree: &mut Vec<Self>, mut filter: impl FnMut(&str) -> bool) {
+    ///
+    /// CodSpeed addition: the filter is also given the entry and the display
+    /// name of each leaf, so that it can match on the URI of the benchmark.
+    pub fn retain(tree: &mut Vec<Self>, mut filter: impl FnMut(&str, AnyBenchEntry, &str) -> bool) {
         fn retain(
             tree: &mut Vec<EntryTree>,
             parent_path: &str,
-            filter: &mut impl FnMut(&str) -> bool,
+            filter: &mut impl FnMut(&str, AnyBenchEntry, &str) -> bool,
         ) {
             tree.retain_mut(|subtree| {
                 let subtree_path: String;
@@ -181,10 +184,12 @@ impl<'a> EntryTree<'a> {
                         !children.is_empty()
                     }
 
-                    EntryTree::Leaf { args: None, .. } => filter(subtree_path),
+                    EntryTree::Leaf { entry, args: None } => {
+                        filter(subtree_path, *entry, entry.display_name())
+                    }
 
-                    EntryTree::Leaf { args: Some(args), .. } => {
-                        args.retain(|arg| filter(&format!("{subtree_path}::{arg}")));
+                    EntryTree::Leaf { entry, args: Some(args) } => {
+                        args.retain(|arg| filter(&format!("{subtree_path}::{arg}"), *entry, arg));
 
                         // If no arguments exist, filter out this leaf.
                         !args.is_empty()
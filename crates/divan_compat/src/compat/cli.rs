@@ -16,5 +16,12 @@ pub(crate) fn command() -> Command {
                 .help("Only run benchmarks whose names match this pattern")
                 .action(ArgAction::Append),
         )
+        .arg(
+            option("skip")
+                .value_name("FILTER")
+                .help("Skip benchmarks whose names match this pattern")
+                .action(ArgAction::Append),
+        )
         .arg(flag("exact").help("Filter benchmarks by exact name rather than by pattern"))
+        .arg(flag("list").help("Lists benchmarks"))
 }
@@ -155,7 +155,7 @@ pub mod counter {
         }
     }
 }
-use codspeed::codspeed::CodSpeed;
+use codspeed::codspeed::{CodSpeed, MessageFormat};
 use config::Filter;
 use entry::AnyBenchEntry;
 use regex::Regex;
@@ -184,12 +184,13 @@ pub fn main() {
     // codspeed URI from entry metadata directly.
 
     // 3. Filtering
+    let mut command = cli::command();
+    let matches = command.get_matches_mut();
+    let is_list = matches.get_flag("list");
     let should_run_benchmark_from_filters = {
-        let mut command = cli::command();
-        let matches = command.get_matches_mut();
         let is_exact = matches.get_flag("exact");
 
-        let parse_filter = |filter: &String| {
+        let mut parse_filter = |filter: &String| {
             if is_exact {
                 Filter::Exact(filter.to_owned())
             } else {
@@ -205,14 +206,19 @@ pub fn main() {
 
         let filters: Option<Vec<Filter>> = matches
             .get_many::<String>("filter")
-            .map(|arg_filters| arg_filters.map(parse_filter).collect());
+            .map(|arg_filters| arg_filters.map(&mut parse_filter).collect());
+        let skip_filters: Vec<Filter> = matches
+            .get_many::<String>("skip")
+            .map(|arg_filters| arg_filters.map(&mut parse_filter).collect())
+            .unwrap_or_default();
 
         move |uri: &str| {
-            if let Some(filters) = filters.as_ref() {
+            let is_included = if let Some(filters) = filters.as_ref() {
                 filters.iter().any(|filter| filter.is_match(uri))
             } else {
                 true
-            }
+            };
+            is_included && !skip_filters.iter().any(|filter| filter.is_match(uri))
         }
     };
 
@@ -224,8 +230,14 @@ pub fn main() {
 
         if let Some(options) = &meta.bench_options {
             if let Some(true) = options.ignore {
+                if is_list {
+                    continue;
+                }
                 let uri = uri::generate(&entry, entry.display_name());
-                println!("Skipped: {uri}");
+                match MessageFormat::from_env() {
+                    MessageFormat::Human => println!("Skipped: {uri}"),
+                    MessageFormat::Json => codspeed.borrow_mut().skip_benchmark(&uri, "ignored"),
+                }
                 continue;
             }
         }
@@ -237,6 +249,11 @@ pub fn main() {
                     continue;
                 }
 
+                if is_list {
+                    println!("{uri}: benchmark");
+                    continue;
+                }
+
                 bench_fn(bench::Bencher::new(&codspeed, uri));
             }
             entry::BenchEntryRunner::Args(bench_runner) => {
@@ -249,6 +266,11 @@ pub fn main() {
                         continue;
                     }
 
+                    if is_list {
+                        println!("{uri}: benchmark");
+                        continue;
+                    }
+
                     let bencher = bench::Bencher::new(&codspeed, uri);
 
                     bench_runner.bench(bencher, arg_index);